@@ -1,9 +1,12 @@
-use crate::nodes::{NodeViewer, Nodes};
+use crate::nodes::{self, dsl, export, files, NodeViewer, Nodes, Numeric, Transport};
 use egui::Id;
 use egui_snarl::Snarl;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Identifier of the app, which also names the directory of the saved state
+pub const APP_ID: &str = "Nodes";
+
 #[derive(Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct App {
@@ -11,6 +14,11 @@ pub struct App {
     snarl_state: SnarlState,
     /// Presets manager
     presets_manager: PresetsManager,
+    /// Playback of the time nodes
+    transport: Transport,
+    /// Number of times the graph was evaluated, a new draw for Random nodes that reseed
+    #[serde(skip)]
+    evaluations: u64,
     /// Window states
     #[serde(skip)]
     window_state: WindowState,
@@ -23,11 +31,23 @@ pub struct SnarlState {
     snarl: Snarl<Nodes>,
     /// The optional ID of the snarl UI element
     ui_id: Option<Id>,
+    /// Number backend and display precision of the graph
+    numeric: Numeric,
 }
 
 #[derive(Default)]
 pub struct WindowState {
     presets: bool,
+    export: bool,
+    /// Text of the last export
+    export_text: String,
+    import: bool,
+    /// Graph text to import
+    import_text: String,
+    /// Error of the last import
+    import_error: Option<String>,
+    /// Error of the last dropped files
+    drop_error: Option<String>,
 }
 
 #[derive(Default, Deserialize, Serialize)]
@@ -45,6 +65,8 @@ impl App {
     /// Called once before the first frame
     pub fn new(cc: &eframe::CreationContext) -> Self {
         egui_extras::install_image_loaders(&cc.egui_ctx);
+        cc.egui_ctx
+            .add_bytes_loader(std::sync::Arc::new(files::DataUriLoader));
         cc.egui_ctx.style_mut(|style| style.animation_time *= 1.5);
         cc.egui_ctx.set_visuals(egui::Visuals {
             dark_mode: true,
@@ -116,6 +138,104 @@ impl App {
                 });
             });
     }
+
+    /// Window: Export
+    fn window_export(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Export")
+            .open(&mut self.window_state.export)
+            .show(ctx, |ui| {
+                if ui.button("Copy").clicked() {
+                    ui.ctx().copy_text(self.window_state.export_text.clone());
+                }
+
+                ui.separator();
+
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::TextEdit::multiline(&mut self.window_state.export_text.as_str())
+                        .code_editor()
+                        .desired_width(f32::INFINITY)
+                        .show(ui);
+                });
+            });
+    }
+
+    /// Window: Import
+    fn window_import(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Import")
+            .open(&mut self.window_state.import)
+            .show(ctx, |ui| {
+                ui.label("Replaces the graph with nodes described in text, one per statement:");
+                ui.code("a = Number(3); b = Expr(\"a * x + 1\", x = a); Sink(b)");
+
+                if ui.button("Import").clicked() {
+                    match dsl::import(&self.window_state.import_text) {
+                        Ok(snarl) => {
+                            self.snarl_state.snarl = snarl;
+                            self.window_state.import_error = None;
+                        }
+                        Err(err) => self.window_state.import_error = Some(err.to_string()),
+                    }
+                }
+                if let Some(err) = &self.window_state.import_error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+
+                ui.separator();
+
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::TextEdit::multiline(&mut self.window_state.import_text)
+                        .code_editor()
+                        .desired_width(f32::INFINITY)
+                        .show(ui);
+                });
+            });
+    }
+
+    /// Opens the export window with the graph in `format`
+    fn export(&mut self, format: export::Format) {
+        self.window_state.export_text = format
+            .export(&self.snarl_state.snarl, self.snarl_state.numeric.precision)
+            .unwrap_or_else(|err| format!("Error: {err}"));
+        self.window_state.export = true;
+    }
+
+    /// Adds a node for each file dropped on the canvas, placed under the pointer
+    fn insert_dropped_files(&mut self, ctx: &egui::Context) {
+        let (dropped, pointer) =
+            ctx.input(|input| (input.raw.dropped_files.clone(), input.pointer.latest_pos()));
+        if dropped.is_empty() {
+            return;
+        }
+
+        let pointer = pointer.unwrap_or_else(|| ctx.screen_rect().center());
+        let mut pos = nodes::graph_pos(ctx, pointer);
+        let mut errors = Vec::new();
+        for file in &dropped {
+            match files::dropped_node(file) {
+                Ok(node) => {
+                    self.snarl_state.snarl.insert_node(pos, node);
+                    pos.y += 100.0;
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+        self.window_state.drop_error = (!errors.is_empty()).then(|| errors.join("\n"));
+    }
+
+    /// Graph of the preset `name`, or the current graph if `None`
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn snarl(&self, name: Option<&str>) -> Option<&Snarl<Nodes>> {
+        match name {
+            Some(name) => self.presets_manager.saved.get(name),
+            None => Some(&self.snarl_state.snarl),
+        }
+    }
+
+    /// Display precision of the current graph, which presets are shown with as well
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) const fn precision(&self) -> u8 {
+        self.snarl_state.numeric.precision
+    }
 }
 
 impl eframe::App for App {
@@ -131,21 +251,59 @@ impl eframe::App for App {
                 if ui.button("Presets").clicked() {
                     self.window_state.presets = !self.window_state.presets;
                 }
+                if ui.button("Import").clicked() {
+                    self.window_state.import = !self.window_state.import;
+                }
+                ui.menu_button("Export", |ui| {
+                    for format in export::Format::ALL {
+                        if ui.button(format.name()).clicked() {
+                            self.export(format);
+                            ui.close_menu();
+                        }
+                    }
+                });
                 if ui.button("Clear").clicked() {
                     self.snarl_state.snarl = Snarl::default();
                 }
+                ui.menu_button("Numbers", |ui| self.snarl_state.numeric.show(ui));
+                ui.separator();
+                self.transport.show(ui);
+                if let Some(err) = &self.window_state.drop_error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                    if ui.small_button("✖").clicked() {
+                        self.window_state.drop_error = None;
+                    }
+                }
             });
         });
 
+        self.transport.advance(ctx);
+        nodes::set_clock(&mut self.snarl_state.snarl, self.transport.clock);
+        self.evaluations = self.evaluations.wrapping_add(1);
+        nodes::set_evaluation(&mut self.snarl_state.snarl, self.evaluations);
+        nodes::set_numeric(&mut self.snarl_state.snarl, self.snarl_state.numeric);
+
         egui::CentralPanel::default().show(ctx, |ui| {
             self.snarl_state.ui_id = Some(ui.id());
+            let mut viewer = NodeViewer {
+                precision: self.snarl_state.numeric.precision,
+            };
             self.snarl_state
                 .snarl
-                .show(&mut NodeViewer, &crate::nodes::snarl_style(), "snarl", ui);
+                .show(&mut viewer, &crate::nodes::snarl_style(), "snarl", ui);
         });
+        self.insert_dropped_files(ctx);
 
         if self.window_state.presets {
             self.window_presets(ctx);
         }
+
+        if self.window_state.export {
+            self.window_export(ctx);
+        }
+
+        if self.window_state.import {
+            self.window_import(ctx);
+        }
     }
 }
@@ -0,0 +1,50 @@
+//! Command line interface, for exporting saved graphs without opening the window.
+
+use std::collections::HashMap;
+
+use crate::{nodes::export::Format, App, APP_ID};
+
+const USAGE: &str = "Usage: nodes export <rust|dot|mermaid|text> [preset]";
+
+/// Runs the command given in `args`, without the program name.
+///
+/// Returns `None` if there is no command and the app should start instead.
+pub fn run(mut args: impl Iterator<Item = String>) -> Option<Result<String, String>> {
+    let command = args.next()?;
+
+    Some(match command.as_str() {
+        "export" => export(args),
+        "help" | "-h" | "--help" => Ok(format!("{USAGE}\n")),
+        _ => Err(format!("Unknown command {command:?}\n{USAGE}")),
+    })
+}
+
+/// Exports the current graph, or a preset, of the saved app state.
+fn export(mut args: impl Iterator<Item = String>) -> Result<String, String> {
+    let format = args.next().ok_or(USAGE)?;
+    let format =
+        Format::from_name(&format).ok_or_else(|| format!("Unknown format {format:?}\n{USAGE}"))?;
+    let preset = args.next();
+
+    let app = load()?;
+    let snarl = app
+        .snarl(preset.as_deref())
+        .ok_or_else(|| format!("No preset named {:?}", preset.unwrap_or_default()))?;
+
+    format.export(snarl, app.precision())
+}
+
+/// Loads the app state saved by the last run of the app.
+fn load() -> Result<App, String> {
+    let path = eframe::storage_dir(APP_ID)
+        .ok_or("Failed to find the app data directory")?
+        .join("app.ron");
+    let text = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+
+    let storage = ron::from_str::<HashMap<String, String>>(&text)
+        .map_err(|err| format!("Failed to decode {}: {err}", path.display()))?;
+    let app = storage.get(eframe::APP_KEY).ok_or("No saved app state")?;
+
+    ron::from_str(app).map_err(|err| format!("Failed to decode app state: {err}"))
+}
@@ -1,3 +1,6 @@
 mod app;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
 mod nodes;
-pub use app::App;
+pub use app::{App, APP_ID};
+pub use nodes::{register, Image, NodeKind, Port, PortType, Value};
@@ -5,6 +5,17 @@
 fn main() -> eframe::Result {
     env_logger::init();
 
+    if let Some(result) = nodes::cli::run(std::env::args().skip(1)) {
+        match result {
+            Ok(output) => print!("{output}"),
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([400.0, 300.0])
@@ -16,7 +27,7 @@ fn main() -> eframe::Result {
         ..Default::default()
     };
     eframe::run_native(
-        "Nodes",
+        nodes::APP_ID,
         native_options,
         Box::new(|cc| Ok(Box::new(nodes::App::new(cc)))),
     )
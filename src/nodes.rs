@@ -1,21 +1,346 @@
 #![allow(clippy::use_self)]
 
+/// Declares the operations of a node family with their names and descriptions.
+///
+/// Each operation is written as `Variant: "Name", "Description";`, with `as "Ident"` after the
+/// variant if its name in graph text is not the variant name.
+macro_rules! node_ops {
+    (@ident $variant:ident $ident:literal) => {
+        $ident
+    };
+    (@ident $variant:ident) => {
+        stringify!($variant)
+    };
+    (
+        $(#[$attr:meta])*
+        pub enum $op:ident {
+            $(
+                $(#[$variant_attr:meta])*
+                $variant:ident $(as $ident:literal)?: $name:literal, $description:literal;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+        pub enum $op {
+            $(
+                $(#[$variant_attr])*
+                $variant,
+            )*
+        }
+
+        impl $op {
+            pub const ALL: [$op; [$(stringify!($variant)),*].len()] = [$($op::$variant),*];
+
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $($op::$variant => $name,)*
+                }
+            }
+
+            /// Name of the node kind in graph text.
+            pub const fn ident(self) -> &'static str {
+                match self {
+                    $($op::$variant => node_ops!(@ident $variant $($ident)?),)*
+                }
+            }
+
+            /// Finds operation by its name in graph text.
+            pub fn from_ident(ident: &str) -> Option<Self> {
+                Self::ALL.into_iter().find(|op| op.ident() == ident)
+            }
+
+            pub const fn description(self) -> &'static str {
+                match self {
+                    $($op::$variant => $description,)*
+                }
+            }
+        }
+    };
+}
+
+mod complex;
+pub mod dsl;
+pub mod export;
+pub mod files;
+mod image;
+mod interval;
+mod json;
+mod kind;
+mod list;
+mod logic;
+mod number;
+mod numeric;
+mod random;
+mod regex;
+mod script;
+mod slider;
+mod string;
+mod table;
+mod time;
+mod units;
+mod vector;
+
 use std::collections::HashMap;
 
 use egui::{Color32, Stroke, Ui, Vec2};
 use egui_snarl::{
     ui::{
         AnyPins, BackgroundPattern, Grid, NodeLayout, PinInfo, PinPlacement, SnarlStyle,
-        SnarlViewer, WireStyle,
+        SnarlViewer, Viewport, WireStyle,
     },
     InPin, InPinId, NodeId, OutPin, OutPinId, Snarl,
 };
+use syn::parse::Parser as _;
+
+use complex::{ComplexNode, ComplexOp};
+use image::{ImageNode, ImageOp, ShowImageNode};
+use interval::IntervalNode;
+use json::{JsonNode, JsonOp};
+use kind::CustomNode;
+use list::{ListNode, ListOp};
+use logic::{LogicNode, LogicOp};
+use number::NumberNode;
+use numeric::{Backend, Real};
+use random::{RandomNode, RandomOp};
+use regex::RegexNode;
+use script::ScriptNode;
+use slider::SliderNode;
+use string::{StringNode, StringOp};
+use table::TableNode;
+use time::{Clock, KeyframesNode, TimeNode, TimeOp};
+use units::Quantity;
+use vector::{VectorNode, VectorOp};
+
+pub use image::Image;
+pub use kind::{register, NodeKind, Port, PortType};
+pub use numeric::Numeric;
+pub use time::Transport;
 
 const STRING_COLOR: Color32 = Color32::from_rgb(0x00, 0xb0, 0x00);
 const NUMBER_COLOR: Color32 = Color32::from_rgb(0xb0, 0x00, 0x00);
 const IMAGE_COLOR: Color32 = Color32::from_rgb(0xb0, 0x00, 0xb0);
+const BOOL_COLOR: Color32 = Color32::from_rgb(0x00, 0x70, 0xd0);
+const VECTOR_COLOR: Color32 = Color32::from_rgb(0xd0, 0x80, 0x00);
+const RGBA_COLOR: Color32 = Color32::from_rgb(0xe0, 0xe0, 0xe0);
+const LIST_COLOR: Color32 = Color32::from_rgb(0x00, 0xa0, 0xa0);
+const JSON_COLOR: Color32 = Color32::from_rgb(0x80, 0x60, 0xc0);
+const PIXELS_COLOR: Color32 = Color32::from_rgb(0xe0, 0x40, 0x80);
 const UNTYPED_COLOR: Color32 = Color32::from_rgb(0xb0, 0xb0, 0xb0);
 
+/// Set of pin types, used to check which pins can be wired together.
+type PinCompat = usize;
+const PIN_NUM: PinCompat = 1;
+const PIN_STR: PinCompat = 2;
+const PIN_IMG: PinCompat = 4;
+const PIN_BOOL: PinCompat = 8;
+const PIN_VEC2: PinCompat = 16;
+const PIN_VEC3: PinCompat = 32;
+const PIN_VEC4: PinCompat = 64;
+const PIN_COLOR: PinCompat = 128;
+const PIN_LIST: PinCompat = 256;
+const PIN_JSON: PinCompat = 512;
+/// Decoded image, while [`PIN_IMG`] is the URL of one.
+const PIN_PIXELS: PinCompat = 1024;
+const PIN_VEC: PinCompat = PIN_VEC2 | PIN_VEC3 | PIN_VEC4;
+/// Types that are held in a [`Value`].
+const PIN_VALUE: PinCompat =
+    PIN_NUM | PIN_STR | PIN_BOOL | PIN_VEC | PIN_COLOR | PIN_LIST | PIN_JSON | PIN_PIXELS;
+const PIN_SINK: PinCompat = PIN_VALUE | PIN_IMG;
+
+fn pin_out_compat(node: &Nodes, pin: usize) -> PinCompat {
+    match node {
+        Nodes::Sink => 0,
+        Nodes::String(_) => PIN_STR,
+        Nodes::ShowImage(_) => ShowImageNode::out_compat(pin),
+        Nodes::Number(_) | Nodes::Slider(_) | Nodes::Interval(_) | Nodes::ExprNode(_) => PIN_NUM,
+        Nodes::Toggle(_) => PIN_BOOL,
+        Nodes::Color(_) => PIN_COLOR,
+        Nodes::StringOp(string_node) => string_node.out_compat(),
+        Nodes::Regex(_) => {
+            if pin == 0 {
+                PIN_BOOL
+            } else {
+                PIN_STR
+            }
+        }
+        Nodes::Logic(logic_node) => logic_node.out_compat(),
+        Nodes::Vector(vector_node) => vector_node.out_compat(pin),
+        Nodes::List(list_node) => list_node.out_compat(),
+        Nodes::Table(_) => PIN_LIST,
+        Nodes::Json(json_node) => json_node.out_compat(),
+        Nodes::Image(_) => PIN_PIXELS,
+        Nodes::Time(_) | Nodes::Keyframes(_) | Nodes::Random(_) | Nodes::Complex(_) => PIN_NUM,
+        Nodes::Script(script_node) => script_node.out_compat(pin),
+        Nodes::Custom(custom_node) => custom_node.out_compat(pin),
+    }
+}
+
+fn pin_in_compat(node: &Nodes, pin: usize) -> PinCompat {
+    match node {
+        Nodes::Sink => PIN_SINK,
+        Nodes::Number(_)
+        | Nodes::Slider(_)
+        | Nodes::Interval(_)
+        | Nodes::String(_)
+        | Nodes::Toggle(_)
+        | Nodes::Color(_)
+        | Nodes::Table(_)
+        | Nodes::Keyframes(_) => 0,
+        Nodes::ShowImage(_) => PIN_STR,
+        Nodes::ExprNode(_) => {
+            if pin == 0 {
+                PIN_STR
+            } else {
+                PIN_NUM
+            }
+        }
+        Nodes::StringOp(string_node) => string_node.in_compat(pin),
+        Nodes::Regex(_) => PIN_STR,
+        Nodes::Logic(logic_node) => logic_node.in_compat(pin),
+        Nodes::Vector(vector_node) => vector_node.in_compat(pin),
+        Nodes::List(list_node) => list_node.in_compat(pin),
+        Nodes::Json(json_node) => json_node.in_compat(pin),
+        Nodes::Image(image_node) => image_node.in_compat(pin),
+        Nodes::Time(time_node) => time_node.in_compat(pin),
+        Nodes::Random(random_node) => random_node.in_compat(pin),
+        Nodes::Complex(complex_node) => complex_node.in_compat(pin),
+        Nodes::Script(script_node) => script_node.in_compat(pin),
+        Nodes::Custom(custom_node) => custom_node.in_compat(pin),
+    }
+}
+
+/// Value of an input that accepts several types, editable in UI while unconnected.
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Value {
+    Number(f64),
+    String(String),
+    Bool(bool),
+    /// Vector of 2, 3 or 4 components.
+    Vector(Vec<f64>),
+    /// Unmultiplied sRGB color with components in 0..1.
+    Color([f64; 4]),
+    /// List of values of the same type.
+    List(Vec<Value>),
+    /// JSON document.
+    Json(serde_json::Value),
+    /// Decoded image, not saved with the graph.
+    Image(Image),
+}
+
+impl Value {
+    fn compat(&self) -> PinCompat {
+        match self {
+            Value::Number(_) => PIN_NUM,
+            Value::String(_) => PIN_STR,
+            Value::Bool(_) => PIN_BOOL,
+            Value::Vector(ref components) => match components.len() {
+                2 => PIN_VEC2,
+                3 => PIN_VEC3,
+                _ => PIN_VEC4,
+            },
+            Value::Color(_) => PIN_COLOR,
+            Value::List(_) => PIN_LIST,
+            Value::Json(_) => PIN_JSON,
+            Value::Image(_) => PIN_PIXELS,
+        }
+    }
+
+    /// Name of the type shown in UI.
+    pub fn type_name(&self) -> &'static str {
+        match *self {
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Bool(_) => "bool",
+            Value::Vector(ref components) => match components.len() {
+                2 => "vec2",
+                3 => "vec3",
+                _ => "vec4",
+            },
+            Value::Color(_) => "color",
+            Value::List(_) => "list",
+            Value::Json(ref json) => json::type_name(json),
+            Value::Image(_) => "image",
+        }
+    }
+
+    /// Value as number, bools are 1 or 0 and strings are parsed.
+    pub fn number(&self) -> f64 {
+        match *self {
+            Value::Number(value) => value,
+            Value::String(ref value) => value.trim().parse().unwrap_or(0.0),
+            Value::Bool(value) => f64::from(u8::from(value)),
+            Value::Vector(_) | Value::Color(_) | Value::List(_) | Value::Image(_) => 0.0,
+            Value::Json(ref json) => json.as_f64().unwrap_or(0.0),
+        }
+    }
+
+    /// Value as bool, numbers are true unless zero, strings, lists and images unless empty
+    /// and vectors unless all components are zero.
+    pub fn bool(&self) -> bool {
+        match *self {
+            Value::Number(value) => value != 0.0,
+            Value::String(ref value) => !value.is_empty(),
+            Value::Bool(value) => value,
+            Value::Vector(_) | Value::Color(_) => {
+                self.components().iter().any(|&component| component != 0.0)
+            }
+            Value::List(ref items) => !items.is_empty(),
+            Value::Json(ref json) => !json.is_null(),
+            Value::Image(ref image) => !image.is_empty(),
+        }
+    }
+
+    /// Components of vectors and colors, a single one for numbers.
+    pub fn components(&self) -> Vec<f64> {
+        match *self {
+            Value::Vector(ref components) => components.clone(),
+            Value::Color(components) => components.to_vec(),
+            Value::Number(value) => vec![value],
+            Value::String(_)
+            | Value::Bool(_)
+            | Value::List(_)
+            | Value::Json(_)
+            | Value::Image(_) => Vec::new(),
+        }
+    }
+
+    /// Value as string, numbers are rounded to the default precision.
+    pub fn string(&self) -> String {
+        self.text(numeric::DEFAULT_PRECISION)
+    }
+
+    /// Value as shown in UI, numbers are rounded to `precision` decimals.
+    pub fn text(&self, precision: u8) -> String {
+        let digits = Some(usize::from(precision));
+        match *self {
+            Value::Number(value) => format_float(value, digits),
+            Value::String(ref value) => value.clone(),
+            Value::Bool(value) => value.to_string(),
+            Value::Vector(ref components) => {
+                let components = components
+                    .iter()
+                    .map(|&component| format_float(component, digits))
+                    .collect::<Vec<_>>();
+                format!("({})", components.join(", "))
+            }
+            Value::Color(color) => vector::color_hex(color),
+            Value::List(ref items) => {
+                let items = items
+                    .iter()
+                    .map(|item| match *item {
+                        Value::String(ref value) => format!("{value:?}"),
+                        ref item => item.text(precision),
+                    })
+                    .collect::<Vec<_>>();
+                format!("[{}]", items.join(", "))
+            }
+            Value::Json(ref json) => json.to_string(),
+            Value::Image(ref image) => format!("{}×{} image", image.width(), image.height()),
+        }
+    }
+}
+
 pub const fn snarl_style() -> SnarlStyle {
     SnarlStyle {
         node_layout: Some(NodeLayout::Basic),
@@ -56,24 +381,129 @@ pub enum Nodes {
 
     /// Value node with a single output.
     /// The value is editable in UI.
-    Number(f64),
+    Number(NumberNode),
+
+    /// Number value node with a single output, kept within a range.
+    /// The value is editable with a slider.
+    Slider(SliderNode),
+
+    /// Number value node with a tolerance and a single output.
+    /// Expr nodes compute the bounds of their results from it.
+    Interval(IntervalNode),
 
     /// Value node with a single output.
     String(String),
 
-    /// Converts URI to Image
-    ShowImage(String),
+    /// Converts URI to Image.
+    /// It has outputs for the size, format and load status after the URL.
+    ShowImage(ShowImageNode),
 
     /// Expression node with a single output.
     /// It has number of inputs equal to number of variables in the expression.
     ExprNode(ExprNode),
+
+    /// String manipulation node with a single output.
+    StringOp(StringNode),
+
+    /// Matches pattern against the text input.
+    /// It has an output for each capture group after `matched` and `match`.
+    Regex(RegexNode),
+
+    /// Bool value node with a single output.
+    /// The value is toggled with a checkbox.
+    Toggle(bool),
+
+    /// Logic gate, comparison or selection node with a single output.
+    Logic(LogicNode),
+
+    /// Color value node with a single output.
+    /// The value is editable with a color picker.
+    Color([f64; 4]),
+
+    /// Vector math node, with an output for each component when decomposing.
+    Vector(VectorNode),
+
+    /// List node with a single output.
+    List(ListNode),
+
+    /// CSV text with a list output for each column.
+    Table(TableNode),
+
+    /// JSON parsing or query node with a single output.
+    Json(JsonNode),
+
+    /// Image loading or processing node with a single output.
+    Image(ImageNode),
+
+    /// Time, frame or LFO node with a single output, driven by the transport.
+    Time(TimeNode),
+
+    /// Curve through keys edited in the node, read at the transport time.
+    Keyframes(KeyframesNode),
+
+    /// Seeded random number node with a single output.
+    Random(RandomNode),
+
+    /// Builds a complex number, or outputs the parts of one.
+    Complex(ComplexNode),
+
+    /// Script with the inputs and outputs declared in its header.
+    Script(ScriptNode),
+
+    /// Node of a kind registered by the crate embedding the app.
+    Custom(CustomNode),
 }
 
 impl Nodes {
-    fn number_out(&self) -> f64 {
+    fn number_out(&self, idx: usize) -> f64 {
+        match self {
+            Nodes::Number(number_node) => number_node.value,
+            Nodes::Slider(slider_node) => slider_node.value_out(),
+            Nodes::Interval(interval_node) => interval_node.value,
+            Nodes::ExprNode(expr_node) => expr_node.eval(idx),
+            Nodes::StringOp(string_node) => string_node.number_out(),
+            Nodes::Logic(logic_node) => logic_node.value_out().number(),
+            Nodes::Vector(vector_node) => vector_node.value_out(idx).number(),
+            Nodes::List(list_node) => list_node.value_out().number(),
+            Nodes::Json(json_node) => json_node.value_out().number(),
+            Nodes::ShowImage(show_image) => show_image.value_out(idx).number(),
+            Nodes::Time(time_node) => time_node.value_out().number(),
+            Nodes::Keyframes(keyframes_node) => keyframes_node.value_out().number(),
+            Nodes::Random(random_node) => random_node.value_out().number(),
+            Nodes::Complex(complex_node) => complex_node.value_out(idx).number(),
+            Nodes::Script(script_node) => script_node.value_out(idx).number(),
+            Nodes::Custom(custom_node) => custom_node.value_out(idx).number(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Number output with its unit, in base units.
+    fn quantity_out(&self, idx: usize) -> Quantity {
+        match self {
+            Nodes::Number(number_node) => number_node.quantity_out(),
+            Nodes::Slider(slider_node) => slider_node.quantity_out(),
+            Nodes::Interval(interval_node) => Quantity {
+                bounds: Some(interval_node.bounds()),
+                ..Quantity::number(interval_node.value)
+            },
+            Nodes::ExprNode(expr_node) => expr_node
+                .quantity(idx)
+                .unwrap_or(Quantity::number(f64::NAN)),
+            Nodes::Complex(complex_node) => complex_node
+                .quantity_out(idx)
+                .unwrap_or(Quantity::number(f64::NAN)),
+            _ => Quantity::number(self.number_out(idx)),
+        }
+    }
+
+    fn bool_out(&self, idx: usize) -> bool {
         match self {
-            Nodes::Number(value) => *value,
-            Nodes::ExprNode(expr_node) => expr_node.eval(),
+            Nodes::Toggle(value) => *value,
+            Nodes::Regex(regex_node) if idx == 0 => regex_node.matched(),
+            Nodes::Logic(logic_node) => logic_node.value_out().bool(),
+            Nodes::ShowImage(show_image) => show_image.value_out(idx).bool(),
+            Nodes::Script(script_node) => script_node.value_out(idx).bool(),
+            Nodes::Custom(custom_node) => custom_node.value_out(idx).bool(),
             _ => unreachable!(),
         }
     }
@@ -85,24 +515,105 @@ impl Nodes {
         }
     }
 
-    fn label_in(&mut self, idx: usize) -> &str {
+    fn label_in(&self, idx: usize) -> &str {
         match self {
             Nodes::ShowImage(_) if idx == 0 => "URL",
+            Nodes::ExprNode(_) if idx == 0 => "Text",
             Nodes::ExprNode(expr_node) => &expr_node.bindings[idx - 1],
+            Nodes::StringOp(string_node) => string_node.label_in(idx),
+            Nodes::Regex(_) if idx == 0 => "Pattern",
+            Nodes::Regex(_) => "Text",
+            Nodes::Logic(logic_node) => logic_node.label_in(idx),
+            Nodes::Vector(vector_node) => vector_node.label_in(idx),
+            Nodes::List(list_node) => list_node.label_in(idx),
+            Nodes::Json(json_node) => json_node.label_in(idx),
+            Nodes::Image(image_node) => image_node.label_in(idx),
+            Nodes::Time(time_node) => time_node.label_in(idx),
+            Nodes::Random(random_node) => random_node.label_in(idx),
+            Nodes::Complex(complex_node) => complex_node.label_in(idx),
+            Nodes::Script(script_node) => script_node.label_in(idx),
+            Nodes::Custom(custom_node) => custom_node.label_in(idx),
             _ => unreachable!(),
         }
     }
 
-    fn string_out(&self) -> &str {
+    /// Label of the output pin, empty for nodes with a single unnamed output.
+    fn label_out(&self, idx: usize) -> &str {
+        match self {
+            Nodes::ExprNode(expr_node) => expr_node.label_out(idx),
+            Nodes::Regex(regex_node) => regex_node.label_out(idx),
+            Nodes::Vector(vector_node) => vector_node.label_out(idx),
+            Nodes::Table(table_node) => &table_node.columns[idx],
+            Nodes::ShowImage(_) => ShowImageNode::label_out(idx),
+            Nodes::Complex(complex_node) => complex_node.label_out(idx),
+            Nodes::Script(script_node) => script_node.label_out(idx),
+            Nodes::Custom(custom_node) => custom_node.label_out(idx),
+            _ => "",
+        }
+    }
+
+    /// Color of the output pin.
+    fn out_color(&self, idx: usize) -> Color32 {
+        match self {
+            Nodes::Sink => UNTYPED_COLOR,
+            _ => compat_color(pin_out_compat(self, idx)),
+        }
+    }
+
+    /// Text representation of the output value, numbers rounded to `precision` decimals.
+    fn out_text(&self, idx: usize, precision: u8) -> String {
+        match self {
+            Nodes::Sink => unreachable!("Sink node has no outputs"),
+            Nodes::ShowImage(show_image) if idx == 0 => show_image.uri.clone(),
+            Nodes::ExprNode(_) | Nodes::Interval(_) | Nodes::Complex(_) => {
+                format!("{:.*}", usize::from(precision), self.quantity_out(idx))
+            }
+            _ => match self.value_out(idx) {
+                Value::String(value) => format!("{value:?}"),
+                value => value.text(precision),
+            },
+        }
+    }
+
+    fn string_out(&self, idx: usize) -> String {
         match self {
-            Nodes::String(value) => value,
+            Nodes::String(value) => value.clone(),
+            Nodes::ShowImage(show_image) => show_image.value_out(idx).string(),
+            Nodes::StringOp(string_node) => string_node.string_out(),
+            Nodes::Regex(regex_node) => regex_node.string_out(idx),
+            Nodes::Logic(logic_node) => logic_node.value_out().string(),
+            Nodes::List(list_node) => list_node.value_out().string(),
+            Nodes::Json(json_node) => json_node.value_out().string(),
+            Nodes::Script(script_node) => script_node.value_out(idx).string(),
+            Nodes::Custom(custom_node) => custom_node.value_out(idx).string(),
             _ => unreachable!(),
         }
     }
 
+    fn value_out(&self, idx: usize) -> Value {
+        match (self, pin_out_compat(self, idx)) {
+            (Nodes::Logic(logic_node), _) => logic_node.value_out(),
+            (Nodes::Vector(vector_node), _) => vector_node.value_out(idx),
+            (Nodes::Color(color), _) => Value::Color(*color),
+            (Nodes::List(list_node), _) => list_node.value_out(),
+            (Nodes::Table(table_node), _) => table_node.value_out(idx),
+            (Nodes::Json(json_node), _) => json_node.value_out(),
+            (Nodes::Image(image_node), _) => image_node.value_out(),
+            (Nodes::Time(time_node), _) => time_node.value_out(),
+            (Nodes::Keyframes(keyframes_node), _) => keyframes_node.value_out(),
+            (Nodes::Random(random_node), _) => random_node.value_out(),
+            (Nodes::Complex(complex_node), _) => complex_node.value_out(idx),
+            (Nodes::Script(script_node), _) => script_node.value_out(idx),
+            (Nodes::Custom(custom_node), _) => custom_node.value_out(idx),
+            (_, PIN_NUM) => Value::Number(self.number_out(idx)),
+            (_, PIN_BOOL) => Value::Bool(self.bool_out(idx)),
+            _ => Value::String(self.string_out(idx)),
+        }
+    }
+
     fn string_in(&mut self) -> &mut String {
         match self {
-            Nodes::ShowImage(uri) => uri,
+            Nodes::ShowImage(show_image) => &mut show_image.uri,
             Nodes::ExprNode(expr_node) => &mut expr_node.text,
             _ => unreachable!(),
         }
@@ -114,9 +625,371 @@ impl Nodes {
             _ => unreachable!(),
         }
     }
+
+    fn complex_node(&mut self) -> &mut ComplexNode {
+        match self {
+            Nodes::Complex(complex_node) => complex_node,
+            _ => unreachable!(),
+        }
+    }
+
+    fn string_node(&mut self) -> &mut StringNode {
+        match self {
+            Nodes::StringOp(string_node) => string_node,
+            _ => unreachable!(),
+        }
+    }
+
+    fn regex_node(&mut self) -> &mut RegexNode {
+        match self {
+            Nodes::Regex(regex_node) => regex_node,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Inputs of nodes that cache their values as [`Value`].
+    fn value_inputs(&mut self) -> &mut Vec<Value> {
+        match self {
+            Nodes::StringOp(string_node) => &mut string_node.inputs,
+            Nodes::Logic(logic_node) => &mut logic_node.inputs,
+            Nodes::Vector(vector_node) => &mut vector_node.inputs,
+            Nodes::List(list_node) => &mut list_node.inputs,
+            Nodes::Json(json_node) => &mut json_node.inputs,
+            Nodes::Image(image_node) => &mut image_node.inputs,
+            Nodes::Time(time_node) => &mut time_node.inputs,
+            Nodes::Random(random_node) => &mut random_node.inputs,
+            Nodes::Complex(complex_node) => &mut complex_node.inputs,
+            Nodes::Script(script_node) => &mut script_node.inputs,
+            Nodes::Custom(custom_node) => &mut custom_node.inputs,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Color of pins of a single type.
+const fn compat_color(compat: PinCompat) -> Color32 {
+    match compat {
+        PIN_NUM => NUMBER_COLOR,
+        PIN_STR => STRING_COLOR,
+        PIN_IMG => IMAGE_COLOR,
+        PIN_BOOL => BOOL_COLOR,
+        PIN_VEC2 | PIN_VEC3 | PIN_VEC4 => VECTOR_COLOR,
+        PIN_COLOR => RGBA_COLOR,
+        PIN_LIST => LIST_COLOR,
+        PIN_JSON => JSON_COLOR,
+        PIN_PIXELS => PIXELS_COLOR,
+        _ => UNTYPED_COLOR,
+    }
+}
+
+/// Pin drawn for a single type, strings use axis aligned wires.
+/// Vectors of 3 and 4 components use triangle and square pins, lists use star pins.
+fn compat_pin(compat: PinCompat) -> PinInfo {
+    let pin = match compat {
+        PIN_VEC3 => PinInfo::triangle(),
+        PIN_VEC4 => PinInfo::square(),
+        PIN_LIST => PinInfo::star(),
+        _ => PinInfo::circle(),
+    }
+    .with_fill(compat_color(compat));
+    if compat == PIN_STR {
+        pin.with_wire_style(WireStyle::AxisAligned {
+            corner_radius: 10.0,
+        })
+    } else {
+        pin
+    }
+}
+
+/// Nodes offered by the dropped wire menu, those with a pin of the wire's type are shown.
+fn wire_candidates() -> impl Iterator<Item = (String, Nodes)> {
+    [
+        ("Sink", Nodes::Sink),
+        ("Number", Nodes::Number(NumberNode::new(0.0))),
+        ("Slider", Nodes::Slider(SliderNode::new())),
+        ("Interval", Nodes::Interval(IntervalNode::new())),
+        ("String", Nodes::String(String::new())),
+        ("Expr", Nodes::ExprNode(ExprNode::new())),
+        ("Show Image", Nodes::ShowImage(ShowImageNode::default())),
+        ("Regex", Nodes::Regex(RegexNode::new())),
+        ("Toggle", Nodes::Toggle(false)),
+        ("Color", Nodes::Color([1.0; 4])),
+        ("Table", Nodes::Table(TableNode::new())),
+        ("Keyframes", Nodes::Keyframes(KeyframesNode::new())),
+        ("Script", Nodes::Script(ScriptNode::new())),
+    ]
+    .into_iter()
+    .chain(StringOp::ALL.map(|op| (op.name(), Nodes::StringOp(StringNode::new(op)))))
+    .chain(LogicOp::ALL.map(|op| (op.name(), Nodes::Logic(LogicNode::new(op)))))
+    .chain(VectorOp::ALL.map(|op| (op.name(), Nodes::Vector(VectorNode::new(op)))))
+    .chain(ListOp::ALL.map(|op| (op.name(), Nodes::List(ListNode::new(op)))))
+    .chain(JsonOp::ALL.map(|op| (op.name(), Nodes::Json(JsonNode::new(op)))))
+    .chain(ImageOp::ALL.map(|op| (op.name(), Nodes::Image(ImageNode::new(op)))))
+    .chain(TimeOp::ALL.map(|op| (op.name(), Nodes::Time(TimeNode::new(op)))))
+    .chain(RandomOp::ALL.map(|op| (op.name(), Nodes::Random(RandomNode::new(op)))))
+    .chain(ComplexOp::ALL.map(|op| (op.name(), Nodes::Complex(ComplexNode::new(op)))))
+    .map(|(name, node)| (name.to_owned(), node))
+    .chain(kind::registered().into_iter().filter_map(|(tag, title)| {
+        let kind = kind::create(&tag)?;
+        Some((title, Nodes::Custom(CustomNode::new(kind))))
+    }))
+}
+
+/// Shows value read from a wire, numbers rounded to `precision` decimals.
+fn show_value(ui: &mut Ui, value: &Value, precision: u8) {
+    match *value {
+        Value::String(ref value) => {
+            ui.label(format!("{value:?}"));
+        }
+        Value::Color(color) => {
+            egui::color_picker::show_color(ui, vector::color32(color), ui.spacing().interact_size);
+        }
+        Value::List(ref items) => {
+            ui.label(format!("{} items", items.len()));
+        }
+        Value::Json(ref json) => {
+            let text = json.to_string();
+            ui.label(egui::RichText::new(text).monospace())
+                .on_hover_text(json::type_name(json));
+        }
+        Value::Image(ref image) => {
+            image.show(ui, 128.0);
+        }
+        ref value => {
+            ui.label(value.text(precision));
+        }
+    }
+}
+
+/// Moves wires of the inputs named `old` from pin `offset` onward to the inputs of the same name in `new`,
+/// dropping wires of names that are gone.
+fn rebind_inputs(
+    snarl: &mut Snarl<Nodes>,
+    node: NodeId,
+    offset: usize,
+    old: &[String],
+    new: &[String],
+) {
+    let mut moved = Vec::new();
+    for (idx, name) in old.iter().enumerate() {
+        let old_pin = snarl.in_pin(InPinId {
+            node,
+            input: offset + idx,
+        });
+        if let Some(new_idx) = new.iter().position(|new_name| new_name == name) {
+            let new_pin = InPinId {
+                node,
+                input: offset + new_idx,
+            };
+            moved.extend(old_pin.remotes.iter().map(|&remote| (remote, new_pin)));
+        }
+        snarl.drop_inputs(old_pin.id);
+    }
+
+    for (remote, pin) in moved {
+        snarl.connect(remote, pin);
+    }
+}
+
+/// Moves wires of the outputs named `old` from pin `offset` onward to the outputs of the same name in `new`,
+/// dropping wires of names that are gone.
+fn rebind_outputs(
+    snarl: &mut Snarl<Nodes>,
+    node: NodeId,
+    offset: usize,
+    old: &[String],
+    new: &[String],
+) {
+    let mut moved = Vec::new();
+    let mut dropped = Vec::new();
+    for (idx, name) in old.iter().enumerate() {
+        let old_pin = snarl.out_pin(OutPinId {
+            node,
+            output: offset + idx,
+        });
+        match new.iter().position(|new_name| new_name == name) {
+            Some(new_idx) => {
+                let new_pin = OutPinId {
+                    node,
+                    output: offset + new_idx,
+                };
+                moved.extend(old_pin.remotes.iter().map(|&remote| (new_pin, remote)));
+            }
+            None => dropped.extend(old_pin.remotes.iter().copied()),
+        }
+        snarl.drop_outputs(old_pin.id);
+    }
+
+    for (pin, remote) in moved {
+        snarl.connect(pin, remote);
+    }
+    for remote in dropped {
+        reset_concat_input(snarl, remote);
+    }
+}
+
+/// Shows input of a node that caches its values as [`Value`], with an editor while unconnected.
+fn show_value_input(pin: &InPin, ui: &mut Ui, snarl: &mut Snarl<Nodes>, precision: u8) -> PinInfo {
+    let remote_value = match &*pin.remotes {
+        [] => None,
+        [remote] => Some(snarl[remote.node].value_out(remote.output)),
+        _ => unreachable!("Input has only one wire"),
+    };
+
+    let node = &mut snarl[pin.id.node];
+    let label = node.label_in(pin.id.input);
+    if !label.is_empty() {
+        ui.label(label);
+    }
+
+    let value = &mut node.value_inputs()[pin.id.input];
+    match remote_value {
+        Some(remote_value) => {
+            show_value(ui, &remote_value, precision);
+            *value = remote_value;
+        }
+        None => {
+            let changed = match value {
+                Value::Number(value) => ui.add(egui::DragValue::new(value)).changed(),
+                Value::String(value) => egui::TextEdit::singleline(value)
+                    .clip_text(false)
+                    .desired_width(0.0)
+                    .margin(ui.spacing().item_spacing)
+                    .show(ui)
+                    .response
+                    .changed(),
+                Value::Bool(value) => ui.checkbox(value, "").changed(),
+                Value::Vector(components) => {
+                    let mut changed = false;
+                    for component in components {
+                        changed |= ui
+                            .add(egui::DragValue::new(component).speed(0.01))
+                            .changed();
+                    }
+                    changed
+                }
+                Value::Color(color) => vector::edit_color(ui, color),
+                Value::List(items) => {
+                    ui.label(format!("{} items", items.len()));
+                    false
+                }
+                Value::Json(json) => {
+                    ui.label(json.to_string());
+                    false
+                }
+                Value::Image(image) => {
+                    image.show(ui, 64.0);
+                    false
+                }
+            };
+
+            // Typing into the spare input of Concat adds another one.
+            if let Nodes::StringOp(ref mut string_node) = snarl[pin.id.node] {
+                if changed
+                    && string_node.op == StringOp::Concat
+                    && pin.id.input + 1 == string_node.inputs.len()
+                {
+                    string_node.inputs.push(Value::String(String::new()));
+                }
+            }
+        }
+    }
+
+    compat_pin(snarl[pin.id.node].value_inputs()[pin.id.input].compat())
+}
+
+/// Clears the input of a Concat node after its wire is removed,
+/// then removes trailing empty inputs keeping a single spare one.
+fn reset_concat_input(snarl: &mut Snarl<Nodes>, pin: InPinId) {
+    match snarl.get_node_mut(pin.node) {
+        Some(Nodes::StringOp(string_node))
+            if string_node.op == StringOp::Concat && pin.input < string_node.inputs.len() =>
+        {
+            string_node.inputs[pin.input] = Value::String(String::new());
+        }
+        _ => return,
+    }
+
+    loop {
+        let string_node = snarl[pin.node].string_node();
+        let len = string_node.inputs.len();
+        let spare = len >= 2
+            && matches!(string_node.inputs[len - 2], Value::String(ref value) if value.is_empty());
+        if !spare
+            || !snarl
+                .in_pin(InPinId {
+                    node: pin.node,
+                    input: len - 2,
+                })
+                .remotes
+                .is_empty()
+        {
+            return;
+        }
+        snarl[pin.node].string_node().inputs.pop();
+    }
+}
+
+/// Sets the transport time on the nodes that read it.
+pub fn set_clock(snarl: &mut Snarl<Nodes>, clock: Clock) {
+    for node in snarl.nodes_mut() {
+        match node {
+            Nodes::Time(time_node) => time_node.clock = clock,
+            Nodes::Keyframes(keyframes_node) => keyframes_node.clock = clock,
+            _ => {}
+        }
+    }
+}
+
+/// Sets the number of the current evaluation on the nodes that reseed each evaluation.
+pub fn set_evaluation(snarl: &mut Snarl<Nodes>, evaluation: u64) {
+    for node in snarl.nodes_mut() {
+        if let Nodes::Random(random_node) = node {
+            random_node.evaluation = evaluation;
+        }
+    }
+}
+
+/// Sets the number backend on the Expr nodes.
+pub fn set_numeric(snarl: &mut Snarl<Nodes>, numeric: Numeric) {
+    for node in snarl.nodes_mut() {
+        if let Nodes::ExprNode(expr_node) = node {
+            expr_node.backend = numeric.backend;
+        }
+    }
+}
+
+/// Graph position under the screen position, as of the last frame drawn.
+pub fn graph_pos(ctx: &egui::Context, pos: egui::Pos2) -> egui::Pos2 {
+    let (rect, scale, offset) = ctx.data(|data| data.get_temp(viewport_id())).unwrap_or((
+        egui::Rect::ZERO,
+        1.0,
+        Vec2::ZERO,
+    ));
+    Viewport {
+        rect,
+        scale,
+        offset,
+    }
+    .screen_pos_to_graph(pos)
+}
+
+fn viewport_id() -> egui::Id {
+    egui::Id::new("snarl viewport")
 }
 
-pub struct NodeViewer;
+pub struct NodeViewer {
+    /// Digits shown after the decimal point.
+    pub precision: u8,
+}
+
+impl Default for NodeViewer {
+    fn default() -> Self {
+        NodeViewer {
+            precision: numeric::DEFAULT_PRECISION,
+        }
+    }
+}
 
 impl SnarlViewer<Nodes> for NodeViewer {
     #[inline]
@@ -131,13 +1004,71 @@ impl SnarlViewer<Nodes> for NodeViewer {
             (_, Nodes::Number(_)) => {
                 unreachable!("Number node has no inputs")
             }
+            (_, Nodes::Slider(_)) => {
+                unreachable!("Slider node has no inputs")
+            }
+            (_, Nodes::Interval(_)) => {
+                unreachable!("Interval node has no inputs")
+            }
             (_, Nodes::String(_)) => {
                 unreachable!("String node has no inputs")
             }
-            (Nodes::Number(_), Nodes::ShowImage(_)) => {
-                return;
+            (_, Nodes::Toggle(_)) => {
+                unreachable!("Toggle node has no inputs")
+            }
+            (_, Nodes::Color(_)) => {
+                unreachable!("Color node has no inputs")
+            }
+            (_, Nodes::Table(_)) => {
+                unreachable!("Table node has no inputs")
+            }
+            (_, Nodes::Keyframes(_)) => {
+                unreachable!("Keyframes node has no inputs")
             }
-            (Nodes::ShowImage(_), Nodes::ShowImage(_)) => {
+            (
+                from_node @ (Nodes::Slider(_)
+                | Nodes::Interval(_)
+                | Nodes::StringOp(_)
+                | Nodes::Regex(_)
+                | Nodes::Toggle(_)
+                | Nodes::Logic(_)
+                | Nodes::Color(_)
+                | Nodes::Vector(_)
+                | Nodes::List(_)
+                | Nodes::Table(_)
+                | Nodes::Json(_)
+                | Nodes::Image(_)
+                | Nodes::ShowImage(_)
+                | Nodes::Time(_)
+                | Nodes::Keyframes(_)
+                | Nodes::Random(_)
+                | Nodes::Complex(_)
+                | Nodes::Script(_)
+                | Nodes::Custom(_)),
+                to_node,
+            )
+            | (
+                from_node,
+                to_node @ (Nodes::StringOp(_)
+                | Nodes::Regex(_)
+                | Nodes::Logic(_)
+                | Nodes::Vector(_)
+                | Nodes::List(_)
+                | Nodes::Json(_)
+                | Nodes::Image(_)
+                | Nodes::Time(_)
+                | Nodes::Random(_)
+                | Nodes::Complex(_)
+                | Nodes::Script(_)
+                | Nodes::Custom(_)),
+            ) => {
+                if pin_out_compat(from_node, from.id.output) & pin_in_compat(to_node, to.id.input)
+                    == 0
+                {
+                    return;
+                }
+            }
+            (Nodes::Number(_), Nodes::ShowImage(_)) => {
                 return;
             }
             (Nodes::String(_), Nodes::ShowImage(_)) => {}
@@ -153,9 +1084,6 @@ impl SnarlViewer<Nodes> for NodeViewer {
             (Nodes::String(_), Nodes::ExprNode(_)) => {
                 return;
             }
-            (Nodes::ShowImage(_), Nodes::ExprNode(_)) => {
-                return;
-            }
             (Nodes::ExprNode(_), Nodes::ShowImage(_)) => {
                 return;
             }
@@ -166,30 +1094,111 @@ impl SnarlViewer<Nodes> for NodeViewer {
         }
 
         snarl.connect(from.id, to.id);
+
+        if let Nodes::StringOp(ref mut string_node) = snarl[to.id.node] {
+            if string_node.op == StringOp::Concat && to.id.input + 1 == string_node.inputs.len() {
+                string_node.inputs.push(Value::String(String::new()));
+            }
+        }
+    }
+
+    fn disconnect(&mut self, from: &OutPin, to: &InPin, snarl: &mut Snarl<Nodes>) {
+        snarl.disconnect(from.id, to.id);
+        reset_concat_input(snarl, to.id);
+    }
+
+    fn drop_inputs(&mut self, pin: &InPin, snarl: &mut Snarl<Nodes>) {
+        snarl.drop_inputs(pin.id);
+        reset_concat_input(snarl, pin.id);
+    }
+
+    fn drop_outputs(&mut self, pin: &OutPin, snarl: &mut Snarl<Nodes>) {
+        snarl.drop_outputs(pin.id);
+        for &remote in &pin.remotes {
+            reset_concat_input(snarl, remote);
+        }
     }
 
     fn title(&mut self, node: &Nodes) -> String {
         match node {
             Nodes::Sink => "Sink".to_owned(),
             Nodes::Number(_) => "Number".to_owned(),
+            Nodes::Slider(_) => "Slider".to_owned(),
+            Nodes::Interval(_) => "Interval".to_owned(),
             Nodes::String(_) => "String".to_owned(),
             Nodes::ShowImage(_) => "Show Image".to_owned(),
             Nodes::ExprNode(_) => "Expr".to_owned(),
+            Nodes::StringOp(ref string_node) => string_node.op.name().to_owned(),
+            Nodes::Regex(_) => "Regex".to_owned(),
+            Nodes::Toggle(_) => "Toggle".to_owned(),
+            Nodes::Logic(ref logic_node) => logic_node.op.name().to_owned(),
+            Nodes::Color(_) => "Color".to_owned(),
+            Nodes::Vector(ref vector_node) => vector_node.op.name().to_owned(),
+            Nodes::List(ref list_node) => list_node.op.name().to_owned(),
+            Nodes::Table(_) => "Table".to_owned(),
+            Nodes::Json(ref json_node) => json_node.op.name().to_owned(),
+            Nodes::Image(ref image_node) => image_node.op.name().to_owned(),
+            Nodes::Time(ref time_node) => time_node.op.name().to_owned(),
+            Nodes::Keyframes(_) => "Keyframes".to_owned(),
+            Nodes::Random(ref random_node) => random_node.op.name().to_owned(),
+            Nodes::Complex(ref complex_node) => complex_node.op.name().to_owned(),
+            Nodes::Script(_) => "Script".to_owned(),
+            Nodes::Custom(ref custom_node) => custom_node.kind.title(),
         }
     }
 
     fn inputs(&mut self, node: &Nodes) -> usize {
         match node {
             Nodes::Sink | Nodes::ShowImage(_) => 1,
-            Nodes::Number(_) | Nodes::String(_) => 0,
+            Nodes::Number(_)
+            | Nodes::Slider(_)
+            | Nodes::Interval(_)
+            | Nodes::String(_)
+            | Nodes::Toggle(_)
+            | Nodes::Color(_)
+            | Nodes::Table(_)
+            | Nodes::Keyframes(_) => 0,
             Nodes::ExprNode(expr_node) => 1 + expr_node.bindings.len(),
+            Nodes::StringOp(string_node) => string_node.inputs.len(),
+            Nodes::Regex(_) => 2,
+            Nodes::Logic(logic_node) => logic_node.inputs.len(),
+            Nodes::Vector(vector_node) => vector_node.inputs.len(),
+            Nodes::List(list_node) => list_node.inputs.len(),
+            Nodes::Json(json_node) => json_node.inputs.len(),
+            Nodes::Image(image_node) => image_node.inputs.len(),
+            Nodes::Time(time_node) => time_node.inputs.len(),
+            Nodes::Random(random_node) => random_node.inputs.len(),
+            Nodes::Complex(complex_node) => complex_node.inputs.len(),
+            Nodes::Script(script_node) => script_node.inputs.len(),
+            Nodes::Custom(custom_node) => custom_node.inputs.len(),
         }
     }
 
     fn outputs(&mut self, node: &Nodes) -> usize {
         match node {
             Nodes::Sink => 0,
-            Nodes::Number(_) | Nodes::String(_) | Nodes::ShowImage(_) | Nodes::ExprNode(_) => 1,
+            Nodes::Number(_)
+            | Nodes::Slider(_)
+            | Nodes::Interval(_)
+            | Nodes::String(_)
+            | Nodes::StringOp(_)
+            | Nodes::Toggle(_)
+            | Nodes::Logic(_)
+            | Nodes::Color(_)
+            | Nodes::List(_)
+            | Nodes::Json(_)
+            | Nodes::Image(_)
+            | Nodes::Time(_)
+            | Nodes::Keyframes(_)
+            | Nodes::Random(_) => 1,
+            Nodes::Regex(regex_node) => regex::FIXED_OUTPUTS.len() + regex_node.groups.len(),
+            Nodes::Vector(vector_node) => vector_node.outputs(),
+            Nodes::Complex(complex_node) => complex_node.outputs(),
+            Nodes::Script(script_node) => script_node.outs.len(),
+            Nodes::Custom(custom_node) => custom_node.outs.len(),
+            Nodes::ExprNode(expr_node) => expr_node.expr.outputs().len(),
+            Nodes::Table(table_node) => table_node.columns.len(),
+            Nodes::ShowImage(_) => 1 + image::INFO_OUTPUTS.len(),
         }
     }
 
@@ -213,9 +1222,14 @@ impl SnarlViewer<Nodes> for NodeViewer {
                     }
                     [remote] => match snarl[remote.node] {
                         Nodes::Sink => unreachable!("Sink node has no outputs"),
-                        Nodes::Number(value) => {
+                        Nodes::Number(ref number_node) => {
                             assert_eq!(remote.output, 0, "Number node has only one output");
-                            ui.label(format_float(value));
+                            let mut text =
+                                format_float(number_node.value, Some(usize::from(self.precision)));
+                            if !number_node.unit.is_empty() {
+                                text = format!("{text} {}", number_node.unit);
+                            }
+                            ui.label(text);
                             PinInfo::circle().with_fill(NUMBER_COLOR)
                         }
                         Nodes::String(ref value) => {
@@ -228,21 +1242,50 @@ impl SnarlViewer<Nodes> for NodeViewer {
                                 },
                             )
                         }
-                        Nodes::ExprNode(ref expr) => {
-                            assert_eq!(remote.output, 0, "Expr node has only one output");
-                            ui.label(format_float(expr.eval()));
+                        Nodes::Interval(_) => {
+                            assert_eq!(remote.output, 0, "Interval node has only one output");
+                            ui.label(snarl[remote.node].out_text(0, self.precision));
                             PinInfo::circle().with_fill(NUMBER_COLOR)
                         }
-                        Nodes::ShowImage(ref uri) => {
-                            assert_eq!(remote.output, 0, "ShowImage node has only one output");
-
-                            let image = egui::Image::new(uri)
+                        Nodes::ExprNode(_) | Nodes::Complex(_) => {
+                            ui.label(snarl[remote.node].out_text(remote.output, self.precision));
+                            PinInfo::circle().with_fill(NUMBER_COLOR)
+                        }
+                        Nodes::ShowImage(ref show_image) if remote.output == 0 => {
+                            let image = egui::Image::new(&show_image.uri)
                                 .fit_to_original_size(scale)
                                 .show_loading_spinner(true);
                             ui.add(image);
 
                             PinInfo::circle().with_fill(IMAGE_COLOR)
                         }
+                        Nodes::Slider(_)
+                        | Nodes::StringOp(_)
+                        | Nodes::Regex(_)
+                        | Nodes::Toggle(_)
+                        | Nodes::Logic(_)
+                        | Nodes::Color(_)
+                        | Nodes::Vector(_)
+                        | Nodes::List(_)
+                        | Nodes::Table(_)
+                        | Nodes::Json(_)
+                        | Nodes::Image(_)
+                        | Nodes::ShowImage(_)
+                        | Nodes::Time(_)
+                        | Nodes::Keyframes(_)
+                        | Nodes::Random(_)
+                        | Nodes::Script(_)
+                        | Nodes::Custom(_) => {
+                            let value = snarl[remote.node].value_out(remote.output);
+                            match value {
+                                Value::List(ref items) => {
+                                    list::show_table(ui, pin.id, items, self.precision)
+                                }
+                                Value::Image(ref image) => image.show(ui, 256.0 * scale),
+                                ref value => show_value(ui, value, self.precision),
+                            }
+                            compat_pin(value.compat())
+                        }
                     },
                     _ => unreachable!("Sink input has only one wire"),
                 }
@@ -250,10 +1293,41 @@ impl SnarlViewer<Nodes> for NodeViewer {
             Nodes::Number(_) => {
                 unreachable!("Number node has no inputs")
             }
+            Nodes::Slider(_) => {
+                unreachable!("Slider node has no inputs")
+            }
+            Nodes::Interval(_) => {
+                unreachable!("Interval node has no inputs")
+            }
             Nodes::String(_) => {
                 unreachable!("String node has no inputs")
             }
+            Nodes::Toggle(_) => {
+                unreachable!("Toggle node has no inputs")
+            }
+            Nodes::Color(_) => {
+                unreachable!("Color node has no inputs")
+            }
+            Nodes::Table(_) => {
+                unreachable!("Table node has no inputs")
+            }
+            Nodes::Keyframes(_) => {
+                unreachable!("Keyframes node has no inputs")
+            }
             Nodes::ShowImage(_) => match &*pin.remotes {
+                // Embedded images are too long to edit as text.
+                [] if snarl[pin.id.node].string_in().starts_with("data:") => {
+                    let uri = snarl[pin.id.node].string_in();
+                    let mime = uri["data:".len()..]
+                        .split([';', ','])
+                        .next()
+                        .unwrap_or_default();
+                    ui.label(format!("Embedded {mime}"));
+                    if ui.small_button("✖").on_hover_text("Clear").clicked() {
+                        uri.clear();
+                    }
+                    compat_pin(PIN_STR)
+                }
                 [] => {
                     let input = snarl[pin.id.node].string_in();
                     egui::TextEdit::singleline(input)
@@ -268,7 +1342,7 @@ impl SnarlViewer<Nodes> for NodeViewer {
                     )
                 }
                 [remote] => {
-                    let new_value = snarl[remote.node].string_out().to_owned();
+                    let new_value = snarl[remote.node].string_out(remote.output);
 
                     egui::TextEdit::singleline(&mut &*new_value)
                         .clip_text(false)
@@ -301,7 +1375,7 @@ impl SnarlViewer<Nodes> for NodeViewer {
                         r.changed()
                     }
                     [remote] => {
-                        let new_string = snarl[remote.node].string_out().to_owned();
+                        let new_string = snarl[remote.node].string_out(remote.output);
 
                         egui::TextEdit::singleline(&mut &*new_string)
                             .clip_text(false)
@@ -323,7 +1397,9 @@ impl SnarlViewer<Nodes> for NodeViewer {
                 if changed {
                     let expr_node = snarl[pin.id.node].expr_node();
 
-                    if let Ok(expr) = syn::parse_str(&expr_node.text) {
+                    if let Ok(expr) = Expr::parse_block.parse_str(&expr_node.text) {
+                        let old_outputs = expr_node.expr.output_names();
+                        let new_outputs = expr.output_names();
                         expr_node.expr = expr;
 
                         let values = Iterator::zip(
@@ -345,36 +1421,8 @@ impl SnarlViewer<Nodes> for NodeViewer {
 
                         expr_node.values = new_values;
 
-                        let old_inputs = (0..old_bindings.len())
-                            .map(|idx| {
-                                snarl.in_pin(InPinId {
-                                    node: pin.id.node,
-                                    input: idx + 1,
-                                })
-                            })
-                            .collect::<Vec<_>>();
-
-                        for (idx, name) in old_bindings.iter().enumerate() {
-                            let new_idx =
-                                new_bindings.iter().position(|new_name| *new_name == *name);
-
-                            match new_idx {
-                                None => {
-                                    snarl.drop_inputs(old_inputs[idx].id);
-                                }
-                                Some(new_idx) if new_idx != idx => {
-                                    let new_in_pin = InPinId {
-                                        node: pin.id.node,
-                                        input: new_idx,
-                                    };
-                                    for &remote in &old_inputs[idx].remotes {
-                                        snarl.disconnect(remote, old_inputs[idx].id);
-                                        snarl.connect(remote, new_in_pin);
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
+                        rebind_inputs(snarl, pin.id.node, 1, &old_bindings, &new_bindings);
+                        rebind_outputs(snarl, pin.id.node, 0, &old_outputs, &new_outputs);
                     }
                 }
                 PinInfo::circle()
@@ -390,14 +1438,17 @@ impl SnarlViewer<Nodes> for NodeViewer {
                             let node = &mut snarl[pin.id.node];
                             ui.label(node.label_in(pin.id.input));
                             ui.add(egui::DragValue::new(node.number_in(pin.id.input)));
+                            node.expr_node().set_wired(pin.id.input - 1, None);
                             PinInfo::circle().with_fill(NUMBER_COLOR)
                         }
                         [remote] => {
-                            let new_value = snarl[remote.node].number_out();
+                            let new_value = snarl[remote.node].quantity_out(remote.output);
                             let node = &mut snarl[pin.id.node];
                             ui.label(node.label_in(pin.id.input));
-                            ui.label(format_float(new_value));
-                            *node.number_in(pin.id.input) = new_value;
+                            ui.label(format!("{:.*}", usize::from(self.precision), new_value));
+                            *node.number_in(pin.id.input) = new_value.value.to_f64();
+                            node.expr_node()
+                                .set_wired(pin.id.input - 1, Some(new_value));
                             PinInfo::circle().with_fill(NUMBER_COLOR)
                         }
                         _ => unreachable!("Expr pins has only one wire"),
@@ -407,9 +1458,122 @@ impl SnarlViewer<Nodes> for NodeViewer {
                     PinInfo::circle().with_fill(Color32::BLACK)
                 }
             }
-        }
-    }
-
+            Nodes::StringOp(ref string_node) if pin.id.input >= string_node.inputs.len() => {
+                ui.label("Removed");
+                PinInfo::circle().with_fill(Color32::BLACK)
+            }
+            Nodes::StringOp(ref string_node)
+                if string_node.op == StringOp::Format && pin.id.input == 0 =>
+            {
+                let old_template = string_node.template().to_owned();
+                show_value_input(pin, ui, snarl, self.precision);
+
+                let string_node = snarl[pin.id.node].string_node();
+                let template = string_node.template().to_owned();
+                if template != old_template {
+                    let new_placeholders = string::placeholders(&template);
+                    let values = Iterator::zip(
+                        string_node.placeholders.iter().cloned(),
+                        string_node.inputs.drain(1..),
+                    )
+                    .collect::<HashMap<String, Value>>();
+
+                    string_node
+                        .inputs
+                        .extend(new_placeholders.iter().map(|name| {
+                            values
+                                .get(name)
+                                .cloned()
+                                .unwrap_or_else(|| Value::String(String::new()))
+                        }));
+                    let old_placeholders =
+                        std::mem::replace(&mut string_node.placeholders, new_placeholders.clone());
+
+                    rebind_inputs(snarl, pin.id.node, 1, &old_placeholders, &new_placeholders);
+                }
+
+                compat_pin(PIN_STR)
+            }
+            Nodes::StringOp(_)
+            | Nodes::Logic(_)
+            | Nodes::Vector(_)
+            | Nodes::List(_)
+            | Nodes::Json(_)
+            | Nodes::Image(_)
+            | Nodes::Time(_)
+            | Nodes::Random(_)
+            | Nodes::Script(_)
+            | Nodes::Custom(_) => show_value_input(pin, ui, snarl, self.precision),
+            Nodes::Complex(ref complex_node) => match (complex_node.op, &*pin.remotes) {
+                (ComplexOp::Decompose, [remote]) => {
+                    let quantity = snarl[remote.node].quantity_out(remote.output);
+                    let node = &mut snarl[pin.id.node];
+                    ui.label(node.label_in(pin.id.input));
+                    ui.label(format!("{:.*}", usize::from(self.precision), quantity));
+                    node.complex_node().wired = Some(quantity);
+                    PinInfo::circle().with_fill(NUMBER_COLOR)
+                }
+                _ => {
+                    snarl[pin.id.node].complex_node().wired = None;
+                    show_value_input(pin, ui, snarl, self.precision)
+                }
+            },
+            Nodes::Regex(_) => {
+                let new_value = match &*pin.remotes {
+                    [] => None,
+                    [remote] => Some(snarl[remote.node].string_out(remote.output)),
+                    _ => unreachable!("Regex pins have only one wire"),
+                };
+
+                let node = &mut snarl[pin.id.node];
+                ui.label(node.label_in(pin.id.input));
+                let regex_node = node.regex_node();
+                let mut input = if pin.id.input == 0 {
+                    regex_node.pattern.clone()
+                } else {
+                    regex_node.text.clone()
+                };
+
+                let changed = match new_value {
+                    None => egui::TextEdit::singleline(&mut input)
+                        .clip_text(false)
+                        .desired_width(0.0)
+                        .margin(ui.spacing().item_spacing)
+                        .show(ui)
+                        .response
+                        .changed(),
+                    Some(new_value) => {
+                        egui::TextEdit::singleline(&mut &*new_value)
+                            .clip_text(false)
+                            .desired_width(0.0)
+                            .margin(ui.spacing().item_spacing)
+                            .show(ui);
+                        let changed = new_value != input;
+                        input = new_value;
+                        changed
+                    }
+                };
+
+                if changed && pin.id.input == 0 {
+                    let old_groups = regex_node.groups.clone();
+                    regex_node.set_pattern(input);
+                    let new_groups = regex_node.groups.clone();
+                    rebind_outputs(
+                        snarl,
+                        pin.id.node,
+                        regex::FIXED_OUTPUTS.len(),
+                        &old_groups,
+                        &new_groups,
+                    );
+                } else if changed {
+                    regex_node.text = input;
+                }
+
+                compat_pin(PIN_STR)
+            }
+        }
+    }
+
     #[allow(refining_impl_trait)]
     fn show_output(
         &mut self,
@@ -422,9 +1586,19 @@ impl SnarlViewer<Nodes> for NodeViewer {
             Nodes::Sink => {
                 unreachable!("Sink node has no outputs")
             }
-            Nodes::Number(ref mut value) => {
+            Nodes::Number(ref mut number_node) => {
                 assert_eq!(pin.id.output, 0, "Number node has only one output");
-                ui.add(egui::DragValue::new(value));
+                number_node.show_output(ui);
+                PinInfo::circle().with_fill(NUMBER_COLOR)
+            }
+            Nodes::Slider(ref mut slider_node) => {
+                assert_eq!(pin.id.output, 0, "Slider node has only one output");
+                slider_node.show_output(ui);
+                PinInfo::circle().with_fill(NUMBER_COLOR)
+            }
+            Nodes::Interval(ref mut interval_node) => {
+                assert_eq!(pin.id.output, 0, "Interval node has only one output");
+                interval_node.show_output(ui, self.precision);
                 PinInfo::circle().with_fill(NUMBER_COLOR)
             }
             Nodes::String(ref mut value) => {
@@ -440,16 +1614,91 @@ impl SnarlViewer<Nodes> for NodeViewer {
                         corner_radius: 10.0,
                     })
             }
-            Nodes::ExprNode(ref expr_node) => {
-                let value = expr_node.eval();
-                assert_eq!(pin.id.output, 0, "Expr node has only one output");
-                ui.label(format_float(value));
+            Nodes::ExprNode(_) => {
+                let node = &snarl[pin.id.node];
+                ui.label(node.out_text(pin.id.output, self.precision));
+                ui.label(node.label_out(pin.id.output));
                 PinInfo::circle().with_fill(NUMBER_COLOR)
             }
-            Nodes::ShowImage(_) => {
+            Nodes::ShowImage(ref mut show_image) if pin.id.output == 0 => {
+                show_image.poll(ui.ctx());
                 ui.allocate_at_least(egui::Vec2::ZERO, egui::Sense::hover());
                 PinInfo::circle().with_fill(IMAGE_COLOR)
             }
+            Nodes::ShowImage(_) => {
+                ui.label(snarl[pin.id.node].out_text(pin.id.output, self.precision));
+                compat_pin(ShowImageNode::out_compat(pin.id.output))
+            }
+            Nodes::Toggle(ref mut value) => {
+                assert_eq!(pin.id.output, 0, "Toggle node has only one output");
+                ui.checkbox(value, "");
+                compat_pin(PIN_BOOL)
+            }
+            Nodes::Logic(_) => {
+                assert_eq!(pin.id.output, 0, "Logic nodes have only one output");
+                ui.label(snarl[pin.id.node].out_text(0, self.precision));
+                compat_pin(pin_out_compat(&snarl[pin.id.node], 0))
+            }
+            Nodes::Color(ref mut color) => {
+                assert_eq!(pin.id.output, 0, "Color node has only one output");
+                vector::edit_color(ui, color);
+                compat_pin(PIN_COLOR)
+            }
+            Nodes::List(_) => {
+                assert_eq!(pin.id.output, 0, "List nodes have only one output");
+                let value = snarl[pin.id.node].value_out(0);
+                show_value(ui, &value, self.precision);
+                compat_pin(value.compat())
+            }
+            Nodes::Json(_) => {
+                assert_eq!(pin.id.output, 0, "JSON nodes have only one output");
+                let value = snarl[pin.id.node].value_out(0);
+                ui.weak(value.type_name());
+                compat_pin(value.compat())
+            }
+            Nodes::Time(_) | Nodes::Keyframes(_) | Nodes::Random(_) => {
+                assert_eq!(pin.id.output, 0, "These nodes have only one output");
+                ui.label(format_float(
+                    snarl[pin.id.node].number_out(0),
+                    Some(usize::from(self.precision)),
+                ));
+                compat_pin(PIN_NUM)
+            }
+            Nodes::Image(ref image_node) => {
+                assert_eq!(pin.id.output, 0, "Image nodes have only one output");
+                image_node.poll(ui.ctx());
+                show_value(ui, &image_node.value_out(), self.precision);
+                compat_pin(PIN_PIXELS)
+            }
+            Nodes::Vector(_) | Nodes::Table(_) | Nodes::Script(_) | Nodes::Custom(_) => {
+                let node = &snarl[pin.id.node];
+                let value = node.value_out(pin.id.output);
+                show_value(ui, &value, self.precision);
+                ui.label(node.label_out(pin.id.output));
+                compat_pin(value.compat())
+            }
+            Nodes::Complex(_) => {
+                let node = &snarl[pin.id.node];
+                ui.label(node.out_text(pin.id.output, self.precision));
+                ui.label(node.label_out(pin.id.output));
+                compat_pin(PIN_NUM)
+            }
+            Nodes::StringOp(_) => {
+                assert_eq!(pin.id.output, 0, "String nodes have only one output");
+                ui.label(snarl[pin.id.node].out_text(0, self.precision));
+                compat_pin(pin_out_compat(&snarl[pin.id.node], 0))
+            }
+            Nodes::Regex(ref regex_node) => {
+                if pin.id.output < regex::FIXED_OUTPUTS.len() + regex_node.groups.len() {
+                    let node = &snarl[pin.id.node];
+                    ui.label(node.out_text(pin.id.output, self.precision));
+                    ui.label(node.label_out(pin.id.output));
+                    compat_pin(pin_out_compat(node, pin.id.output))
+                } else {
+                    ui.label("Removed");
+                    PinInfo::circle().with_fill(Color32::BLACK)
+                }
+            }
         }
     }
 
@@ -466,7 +1715,15 @@ impl SnarlViewer<Nodes> for NodeViewer {
     ) {
         ui.label("Add node");
         if ui.button("Number").clicked() {
-            snarl.insert_node(pos, Nodes::Number(0.0));
+            snarl.insert_node(pos, Nodes::Number(NumberNode::new(0.0)));
+            ui.close_menu();
+        }
+        if ui.button("Slider").clicked() {
+            snarl.insert_node(pos, Nodes::Slider(SliderNode::new()));
+            ui.close_menu();
+        }
+        if ui.button("Interval").clicked() {
+            snarl.insert_node(pos, Nodes::Interval(IntervalNode::new()));
             ui.close_menu();
         }
         if ui.button("Expr").clicked() {
@@ -478,9 +1735,118 @@ impl SnarlViewer<Nodes> for NodeViewer {
             ui.close_menu();
         }
         if ui.button("Show Image").clicked() {
-            snarl.insert_node(pos, Nodes::ShowImage(String::new()));
+            snarl.insert_node(pos, Nodes::ShowImage(ShowImageNode::default()));
+            ui.close_menu();
+        }
+        if ui.button("Regex").clicked() {
+            snarl.insert_node(pos, Nodes::Regex(RegexNode::new()));
+            ui.close_menu();
+        }
+        if ui.button("Toggle").clicked() {
+            snarl.insert_node(pos, Nodes::Toggle(false));
+            ui.close_menu();
+        }
+        if ui.button("Color").clicked() {
+            snarl.insert_node(pos, Nodes::Color([1.0; 4]));
+            ui.close_menu();
+        }
+        if ui.button("Table").clicked() {
+            snarl.insert_node(pos, Nodes::Table(TableNode::new()));
+            ui.close_menu();
+        }
+        if ui.button("Script").clicked() {
+            snarl.insert_node(pos, Nodes::Script(ScriptNode::new()));
             ui.close_menu();
         }
+        ui.menu_button("Logic", |ui| {
+            for op in LogicOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::Logic(LogicNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button("Vector", |ui| {
+            for op in VectorOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::Vector(VectorNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button("List", |ui| {
+            for op in ListOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::List(ListNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button("JSON", |ui| {
+            for op in JsonOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::Json(JsonNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button("Image", |ui| {
+            for op in ImageOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::Image(ImageNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button("Time", |ui| {
+            for op in TimeOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::Time(TimeNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+            if ui.button("Keyframes").clicked() {
+                snarl.insert_node(pos, Nodes::Keyframes(KeyframesNode::new()));
+                ui.close_menu();
+            }
+        });
+        ui.menu_button("Random", |ui| {
+            for op in RandomOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::Random(RandomNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button("Complex", |ui| {
+            for op in ComplexOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::Complex(ComplexNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+        });
+        ui.menu_button("String ops", |ui| {
+            for op in StringOp::ALL {
+                if ui.button(op.name()).clicked() {
+                    snarl.insert_node(pos, Nodes::StringOp(StringNode::new(op)));
+                    ui.close_menu();
+                }
+            }
+        });
+        let kinds = kind::registered();
+        if !kinds.is_empty() {
+            ui.menu_button("Custom", |ui| {
+                for (tag, title) in kinds {
+                    if ui.button(title).clicked() {
+                        if let Some(kind) = kind::create(&tag) {
+                            snarl.insert_node(pos, Nodes::Custom(CustomNode::new(kind)));
+                        }
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
         if ui.button("Sink").clicked() {
             snarl.insert_node(pos, Nodes::Sink);
             ui.close_menu();
@@ -502,38 +1868,7 @@ impl SnarlViewer<Nodes> for NodeViewer {
         // In this demo, we create a context-aware node graph menu, and connect a wire
         // dropped on the fly based on user input to a new node created.
         //
-        // In your implementation, you may want to define specifications for each node's
-        // pin inputs and outputs and compatibility to make this easier.
-
-        type PinCompat = usize;
-        const PIN_NUM: PinCompat = 1;
-        const PIN_STR: PinCompat = 2;
-        const PIN_IMG: PinCompat = 4;
-        const PIN_SINK: PinCompat = PIN_NUM | PIN_STR | PIN_IMG;
-
-        const fn pin_out_compat(node: &Nodes) -> PinCompat {
-            match node {
-                Nodes::Sink => 0,
-                Nodes::String(_) => PIN_STR,
-                Nodes::ShowImage(_) => PIN_IMG,
-                Nodes::Number(_) | Nodes::ExprNode(_) => PIN_NUM,
-            }
-        }
-
-        const fn pin_in_compat(node: &Nodes, pin: usize) -> PinCompat {
-            match node {
-                Nodes::Sink => PIN_SINK,
-                Nodes::Number(_) | Nodes::String(_) => 0,
-                Nodes::ShowImage(_) => PIN_STR,
-                Nodes::ExprNode(_) => {
-                    if pin == 0 {
-                        PIN_STR
-                    } else {
-                        PIN_NUM
-                    }
-                }
-            }
-        }
+        // Candidates are filtered by `pin_in_compat` and `pin_out_compat`.
 
         ui.label("Add node");
 
@@ -545,17 +1880,15 @@ impl SnarlViewer<Nodes> for NodeViewer {
                 );
 
                 let src_pin = src_pins[0];
-                let src_out_ty = pin_out_compat(snarl.get_node(src_pin.node).unwrap());
-                let dst_in_candidates = [
-                    ("Sink", (|| Nodes::Sink) as fn() -> Nodes, PIN_SINK),
-                    ("Show Image", || Nodes::ShowImage(String::new()), PIN_STR),
-                    ("Expr", || Nodes::ExprNode(ExprNode::new()), PIN_STR),
-                ];
-
-                for (name, ctor, in_ty) in dst_in_candidates {
+                let src_out_ty =
+                    pin_out_compat(snarl.get_node(src_pin.node).unwrap(), src_pin.output);
+                let dst_in_candidates = wire_candidates();
+
+                for (name, node) in dst_in_candidates {
+                    let in_ty = pin_in_compat(&node, 0);
                     if src_out_ty & in_ty != 0 && ui.button(name).clicked() {
                         // Create new node.
-                        let new_node = snarl.insert_node(pos, ctor());
+                        let new_node = snarl.insert_node(pos, node);
                         let dst_pin = InPinId {
                             node: new_node,
                             input: 0,
@@ -572,18 +1905,12 @@ impl SnarlViewer<Nodes> for NodeViewer {
                     acc | pin_in_compat(snarl.get_node(pin.node).unwrap(), pin.input)
                 });
 
-                let dst_out_candidates = [
-                    ("Number", (|| Nodes::Number(0.)) as fn() -> Nodes, PIN_NUM),
-                    ("String", || Nodes::String(String::new()), PIN_STR),
-                    ("Expr", || Nodes::ExprNode(ExprNode::new()), PIN_NUM),
-                    ("Show Image", || Nodes::ShowImage(String::new()), PIN_IMG),
-                ];
+                let dst_out_candidates = wire_candidates();
 
-                for (name, ctor, out_ty) in dst_out_candidates {
-                    if all_src_types & out_ty != 0 && ui.button(name).clicked() {
+                for (name, new_node) in dst_out_candidates {
+                    let dst_ty = pin_out_compat(&new_node, 0);
+                    if all_src_types & dst_ty != 0 && ui.button(name).clicked() {
                         // Create new node.
-                        let new_node = ctor();
-                        let dst_ty = pin_out_compat(&new_node);
 
                         let new_node = snarl.insert_node(pos, new_node);
                         let dst_pin = OutPinId {
@@ -609,6 +1936,154 @@ impl SnarlViewer<Nodes> for NodeViewer {
         };
     }
 
+    fn has_body(&mut self, node: &Nodes) -> bool {
+        match node {
+            Nodes::Number(_)
+            | Nodes::Slider(_)
+            | Nodes::Table(_)
+            | Nodes::Json(_)
+            | Nodes::Keyframes(_)
+            | Nodes::Script(_) => true,
+            Nodes::Custom(custom_node) => custom_node.kind.has_body(),
+            _ => false,
+        }
+    }
+
+    fn show_body(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Nodes>,
+    ) {
+        match snarl[node] {
+            Nodes::Table(ref mut table_node) => {
+                let old_columns = table_node.columns.clone();
+                if table_node.show_body(ui, node) {
+                    let new_columns = table_node.columns.clone();
+                    rebind_outputs(snarl, node, 0, &old_columns, &new_columns);
+                }
+            }
+            Nodes::Json(ref json_node) => {
+                if let Ok(document) = json_node.document() {
+                    json::show_tree(ui, node, &document);
+                }
+            }
+            Nodes::Keyframes(ref mut keyframes_node) => keyframes_node.show_body(ui, node),
+            Nodes::Script(ref mut script_node) => {
+                let old_ins = script_node.in_keys();
+                let old_outs = script_node.out_keys();
+                let changed = egui::TextEdit::multiline(&mut script_node.text)
+                    .code_editor()
+                    .desired_rows(6)
+                    .show(ui)
+                    .response
+                    .changed();
+                if changed {
+                    script_node.reparse();
+                    let new_ins = script_node.in_keys();
+                    let new_outs = script_node.out_keys();
+                    rebind_inputs(snarl, node, 0, &old_ins, &new_ins);
+                    rebind_outputs(snarl, node, 0, &old_outs, &new_outs);
+                }
+            }
+            Nodes::Custom(ref mut custom_node) => {
+                let old_ins = custom_node.in_keys();
+                let old_outs = custom_node.out_keys();
+                if custom_node.show_body(ui) {
+                    let new_ins = custom_node.in_keys();
+                    let new_outs = custom_node.out_keys();
+                    rebind_inputs(snarl, node, 0, &old_ins, &new_ins);
+                    rebind_outputs(snarl, node, 0, &old_outs, &new_outs);
+                }
+            }
+            Nodes::Number(ref mut number_node) => number_node.show_body(ui),
+            Nodes::Slider(ref mut slider_node) => slider_node.show_body(ui, node),
+            _ => {}
+        }
+    }
+
+    fn has_footer(&mut self, node: &Nodes) -> bool {
+        match node {
+            Nodes::ExprNode(_) => true,
+            Nodes::Regex(regex_node) => regex_node.regex().is_err(),
+            Nodes::List(list_node) => {
+                matches!(list_node.op, ListOp::Map | ListOp::Filter | ListOp::Reduce)
+                    && list_node.expr().is_err()
+            }
+            Nodes::Table(table_node) => table_node.error().is_some(),
+            Nodes::Json(json_node) => json_node.error().is_some(),
+            Nodes::Image(image_node) => image_node.error().is_some(),
+            Nodes::Script(script_node) => {
+                script_node.error().is_some() || !script_node.printed().is_empty()
+            }
+            Nodes::Custom(custom_node) => custom_node.error().is_some(),
+            _ => false,
+        }
+    }
+
+    fn show_footer(
+        &mut self,
+        node: NodeId,
+        _inputs: &[InPin],
+        _outputs: &[OutPin],
+        ui: &mut Ui,
+        _scale: f32,
+        snarl: &mut Snarl<Nodes>,
+    ) {
+        match snarl[node] {
+            Nodes::ExprNode(ref expr_node) => {
+                expr_node.expr.show_math(ui);
+                let errors = (0..expr_node.expr.outputs().len())
+                    .filter_map(|output| expr_node.quantity(output).err());
+                for err in errors {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            Nodes::Regex(ref regex_node) => {
+                if let Err(err) = regex_node.regex() {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            Nodes::List(ref list_node) => {
+                if let Err(err) = list_node.expr() {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            Nodes::Table(ref table_node) => {
+                if let Some(err) = table_node.error() {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            Nodes::Json(ref json_node) => {
+                if let Some(err) = json_node.error() {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            Nodes::Image(ref image_node) => {
+                if let Some(err) = image_node.error() {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            Nodes::Script(ref script_node) => {
+                for line in script_node.printed() {
+                    ui.label(egui::RichText::new(line).monospace());
+                }
+                if let Some(err) = script_node.error() {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            Nodes::Custom(ref custom_node) => {
+                if let Some(err) = custom_node.error() {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+            }
+            _ => {}
+        }
+    }
+
     fn has_node_menu(&mut self, _node: &Nodes) -> bool {
         true
     }
@@ -623,6 +2098,16 @@ impl SnarlViewer<Nodes> for NodeViewer {
         snarl: &mut Snarl<Nodes>,
     ) {
         ui.label("Node menu");
+        if let Nodes::ExprNode(ref expr_node) = snarl[node] {
+            if ui.button("Copy LaTeX").clicked() {
+                ui.ctx().copy_text(expr_node.expr.latex());
+                ui.close_menu();
+            }
+            if ui.button("Inline upstream").clicked() {
+                inline_upstream(snarl, node);
+                ui.close_menu();
+            }
+        }
         if ui.button("Remove").clicked() {
             snarl.remove_node(node);
             ui.close_menu();
@@ -647,17 +2132,94 @@ impl SnarlViewer<Nodes> for NodeViewer {
                 ui.label("Displays anything connected to it");
             }
             Nodes::Number(_) => {
-                ui.label("Outputs integer value");
+                ui.label("Outputs number value in its unit");
+            }
+            Nodes::Slider(_) => {
+                ui.label("Outputs number within the range");
+            }
+            Nodes::Interval(_) => {
+                ui.label("Outputs number known within a tolerance");
             }
             Nodes::String(_) => {
                 ui.label("Outputs string value");
             }
             Nodes::ShowImage(_) => {
-                ui.label("Displays image from URL in input");
+                ui.label("Displays image from URL in input, with outputs for its size, format and load status");
             }
             Nodes::ExprNode(_) => {
-                ui.label("Evaluates algebraic expression with input for each unique variable name");
+                ui.label(
+                    "Evaluates algebraic expressions with input for each unique variable name and output for each result",
+                );
+            }
+            Nodes::StringOp(ref string_node) => {
+                ui.label(string_node.op.description());
+            }
+            Nodes::Regex(_) => {
+                ui.label("Matches regular expression, with output for each capture group");
+            }
+            Nodes::Toggle(_) => {
+                ui.label("Outputs bool value");
+            }
+            Nodes::Logic(ref logic_node) => {
+                ui.label(logic_node.op.description());
+            }
+            Nodes::Color(_) => {
+                ui.label("Outputs color picked in UI");
+            }
+            Nodes::Vector(ref vector_node) => {
+                ui.label(vector_node.op.description());
+            }
+            Nodes::List(ref list_node) => {
+                ui.label(list_node.op.description());
+            }
+            Nodes::Table(_) => {
+                ui.label("Outputs each column of CSV text as a list");
+            }
+            Nodes::Json(ref json_node) => {
+                ui.label(json_node.op.description());
+            }
+            Nodes::Image(ref image_node) => {
+                ui.label(image_node.op.description());
+            }
+            Nodes::Time(ref time_node) => {
+                ui.label(time_node.op.description());
+            }
+            Nodes::Keyframes(_) => {
+                ui.label("Outputs the curve through the keys at the transport time");
+            }
+            Nodes::Random(ref random_node) => {
+                ui.label(random_node.op.description());
             }
+            Nodes::Complex(ref complex_node) => {
+                ui.label(complex_node.op.description());
+            }
+            Nodes::Script(_) => {
+                ui.label("Runs Rhai script with the inputs and outputs declared in its header");
+            }
+            Nodes::Custom(ref custom_node) => {
+                ui.label(custom_node.kind.description());
+            }
+        }
+    }
+
+    /// Records the viewport for [`graph_pos`].
+    fn draw_background(
+        &mut self,
+        background: Option<&BackgroundPattern>,
+        viewport: &Viewport,
+        snarl_style: &SnarlStyle,
+        style: &egui::Style,
+        painter: &egui::Painter,
+        _snarl: &Snarl<Nodes>,
+    ) {
+        painter.ctx().data_mut(|data| {
+            data.insert_temp(
+                viewport_id(),
+                (viewport.rect, viewport.scale, viewport.offset),
+            );
+        });
+        if let Some(background) = background {
+            background.draw(viewport, snarl_style, style, painter);
         }
     }
 
@@ -671,10 +2233,23 @@ impl SnarlViewer<Nodes> for NodeViewer {
     ) -> egui::Frame {
         match snarl[node] {
             Nodes::Sink => frame.fill(egui::Color32::from_rgb(70, 70, 80)),
-            Nodes::Number(_) => frame.fill(egui::Color32::from_rgb(70, 40, 40)),
+            Nodes::Number(_) | Nodes::Slider(_) | Nodes::Interval(_) => {
+                frame.fill(egui::Color32::from_rgb(70, 40, 40))
+            }
             Nodes::String(_) => frame.fill(egui::Color32::from_rgb(40, 70, 40)),
             Nodes::ShowImage(_) => frame.fill(egui::Color32::from_rgb(40, 40, 70)),
             Nodes::ExprNode(_) => frame.fill(egui::Color32::from_rgb(70, 66, 40)),
+            Nodes::StringOp(_) => frame.fill(egui::Color32::from_rgb(40, 60, 50)),
+            Nodes::Regex(_) => frame.fill(egui::Color32::from_rgb(40, 60, 70)),
+            Nodes::Toggle(_) | Nodes::Logic(_) => frame.fill(egui::Color32::from_rgb(40, 50, 80)),
+            Nodes::Color(_) | Nodes::Vector(_) => frame.fill(egui::Color32::from_rgb(80, 60, 30)),
+            Nodes::List(_) | Nodes::Table(_) => frame.fill(egui::Color32::from_rgb(30, 70, 70)),
+            Nodes::Json(_) => frame.fill(egui::Color32::from_rgb(60, 45, 80)),
+            Nodes::Image(_) => frame.fill(egui::Color32::from_rgb(70, 40, 60)),
+            Nodes::Time(_) | Nodes::Keyframes(_) => frame.fill(egui::Color32::from_rgb(70, 55, 35)),
+            Nodes::Random(_) => frame.fill(egui::Color32::from_rgb(55, 70, 45)),
+            Nodes::Complex(_) => frame.fill(egui::Color32::from_rgb(70, 66, 40)),
+            Nodes::Script(_) | Nodes::Custom(_) => frame.fill(egui::Color32::from_rgb(60, 60, 60)),
         }
     }
 }
@@ -684,6 +2259,12 @@ pub struct ExprNode {
     text: String,
     bindings: Vec<String>,
     values: Vec<f64>,
+    /// Value of each connected binding with its unit and bounds, copied from the remote.
+    #[serde(skip)]
+    wired: Vec<Option<Quantity>>,
+    /// Representation the expression is evaluated with, set from the graph.
+    #[serde(skip)]
+    backend: Backend,
     expr: Expr,
 }
 
@@ -693,22 +2274,224 @@ impl ExprNode {
             text: "0".to_string(),
             bindings: Vec::new(),
             values: Vec::new(),
+            wired: Vec::new(),
+            backend: Backend::Float,
             expr: Expr::Val(0.0),
         }
     }
 
-    fn eval(&self) -> f64 {
-        self.expr.eval(&self.bindings, &self.values)
+    /// Parses `text` into a node with all bindings set to zero.
+    fn with_text(text: String) -> syn::Result<Self> {
+        let expr = Expr::parse_block.parse_str(&text)?;
+        let mut bindings = Vec::new();
+        expr.extend_bindings(&mut bindings);
+
+        Ok(ExprNode {
+            text,
+            values: vec![0.0; bindings.len()],
+            wired: Vec::new(),
+            backend: Backend::Float,
+            bindings,
+            expr,
+        })
+    }
+
+    fn eval(&self, output: usize) -> f64 {
+        self.quantity(output)
+            .map_or(f64::NAN, |quantity| quantity.value.to_f64())
+    }
+
+    /// Value of the output with its unit, or the error of mixing incompatible units or overflowing an exact backend.
+    fn quantity(&self, output: usize) -> Result<Quantity, String> {
+        let args = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(idx, &value)| {
+                self.wired
+                    .get(idx)
+                    .copied()
+                    .flatten()
+                    .unwrap_or(Quantity::number(value))
+            })
+            .collect::<Vec<_>>();
+        self.expr.outputs()[output].eval_units(&self.bindings, &args, self.backend)
+    }
+
+    /// Label of the output, empty for a single unnamed one.
+    fn label_out(&self, output: usize) -> &str {
+        match self.expr {
+            Expr::Tuple(ref outputs) => &outputs[output].0,
+            _ => "",
+        }
+    }
+
+    fn set_wired(&mut self, idx: usize, quantity: Option<Quantity>) {
+        self.wired.resize(self.bindings.len(), None);
+        if let Some(slot) = self.wired.get_mut(idx) {
+            *slot = quantity;
+        }
+    }
+}
+
+/// Where the value of a binding comes from when inlining upstream expressions.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum InlineSource {
+    /// Binding wired to an output that is not inlined.
+    Wire(OutPinId),
+    /// Unconnected binding with its own value.
+    Free(InPinId),
+}
+
+/// Bindings collected while inlining upstream expressions into one node.
+#[derive(Default)]
+struct InlineScope {
+    /// Unique binding names with their current value and source.
+    bindings: Vec<(String, f64, InlineSource)>,
+    /// Upstream Expr nodes substituted into the expression.
+    inlined: Vec<NodeId>,
+}
+
+impl InlineScope {
+    /// Returns the binding name for `source`, allocating a unique one
+    /// based on `name` the first time the source is seen.
+    fn bind(&mut self, name: &str, value: f64, source: InlineSource) -> String {
+        if let Some((name, ..)) = self.bindings.iter().find(|(.., s)| *s == source) {
+            return name.clone();
+        }
+
+        let mut unique = name.to_owned();
+        let mut suffix = 0;
+        while self.bindings.iter().any(|(n, ..)| *n == unique) {
+            suffix += 1;
+            unique = format!("{name}_{suffix}");
+        }
+
+        self.bindings.push((unique.clone(), value, source));
+        unique
+    }
+
+    fn get(&self, name: &str) -> Option<(f64, InlineSource)> {
+        self.bindings
+            .iter()
+            .find(|(n, ..)| n == name)
+            .map(|&(_, value, source)| (value, source))
+    }
+}
+
+/// Returns expression of the Expr `node` with every upstream Expr node substituted in.
+///
+/// `visiting` holds the nodes currently being expanded, wires forming a cycle are kept as bindings.
+fn inline_expr(
+    snarl: &Snarl<Nodes>,
+    node: NodeId,
+    scope: &mut InlineScope,
+    visiting: &mut Vec<NodeId>,
+) -> Expr {
+    let Nodes::ExprNode(ref expr_node) = snarl[node] else {
+        unreachable!("Only Expr nodes can be inlined")
+    };
+
+    let mut substitutions = HashMap::new();
+    for (idx, name) in expr_node.bindings.iter().enumerate() {
+        let pin = InPinId {
+            node,
+            input: idx + 1,
+        };
+
+        let substitution = match *snarl.in_pin(pin).remotes {
+            [remote]
+                if matches!(snarl[remote.node], Nodes::ExprNode(_))
+                    && !visiting.contains(&remote.node) =>
+            {
+                visiting.push(remote.node);
+                let expr = inline_expr(snarl, remote.node, scope, visiting);
+                visiting.pop();
+                let expr = expr.outputs()[remote.output].clone();
+
+                if !scope.inlined.contains(&remote.node) {
+                    scope.inlined.push(remote.node);
+                }
+                expr
+            }
+            [remote] => {
+                let value = snarl[remote.node].number_out(remote.output);
+                Expr::Var(scope.bind(name, value, InlineSource::Wire(remote)))
+            }
+            _ => Expr::Var(scope.bind(name, expr_node.values[idx], InlineSource::Free(pin))),
+        };
+
+        substitutions.insert(name.as_str(), substitution);
+    }
+
+    expr_node.expr.substitute(&substitutions)
+}
+
+/// Substitutes every Expr node wired upstream of the Expr `node` into its expression.
+///
+/// Wires from other nodes are moved to the matching binding,
+/// and inlined nodes that no longer feed anything are removed.
+fn inline_upstream(snarl: &mut Snarl<Nodes>, node: NodeId) {
+    let mut scope = InlineScope::default();
+    let expr = inline_expr(snarl, node, &mut scope, &mut vec![node]);
+
+    if scope.inlined.is_empty() {
+        return;
+    }
+
+    // Text no longer follows a wired string once rewritten.
+    for input in 0..=snarl[node].expr_node().bindings.len() {
+        snarl.drop_inputs(InPinId { node, input });
+    }
+
+    let mut bindings = Vec::new();
+    expr.extend_bindings(&mut bindings);
+
+    let expr_node = snarl[node].expr_node();
+    expr_node.text = expr.to_string();
+    expr_node.values = bindings
+        .iter()
+        .map(|name| scope.get(name).map_or(0.0, |(value, _)| value))
+        .collect();
+    expr_node.bindings = bindings.clone();
+    expr_node.expr = expr;
+
+    for (idx, name) in bindings.iter().enumerate() {
+        if let Some((_, InlineSource::Wire(remote))) = scope.get(name) {
+            snarl.connect(
+                remote,
+                InPinId {
+                    node,
+                    input: idx + 1,
+                },
+            );
+        }
+    }
+
+    // Removing a node frees the nodes feeding it, so repeat until nothing changes.
+    while let Some(idx) = scope.inlined.iter().position(|&inlined| {
+        let outputs = NodeViewer::default().outputs(&snarl[inlined]);
+        (0..outputs).all(|output| {
+            snarl
+                .out_pin(OutPinId {
+                    node: inlined,
+                    output,
+                })
+                .remotes
+                .is_empty()
+        })
+    }) {
+        snarl.remove_node(scope.inlined.swap_remove(idx));
     }
 }
 
-#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 enum UnOp {
     Pos,
     Neg,
 }
 
-#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 enum BinOp {
     Add,
     Sub,
@@ -716,10 +2499,55 @@ enum BinOp {
     Div,
 }
 
-#[derive(Clone, serde::Serialize, serde::Deserialize)]
+/// Function applied to a single operand in expressions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+enum Func {
+    Re,
+    Im,
+    Abs,
+    Arg,
+    Conj,
+}
+
+impl Func {
+    const ALL: [Func; 5] = [Func::Re, Func::Im, Func::Abs, Func::Arg, Func::Conj];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Func::Re => "re",
+            Func::Im => "im",
+            Func::Abs => "abs",
+            Func::Arg => "arg",
+            Func::Conj => "conj",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Func> {
+        Func::ALL.into_iter().find(|func| func.name() == name)
+    }
+
+    fn apply(self, value: Quantity) -> Result<Quantity, String> {
+        match self {
+            Func::Re => Ok(value.re()),
+            Func::Im => Ok(value.im()),
+            Func::Abs => value.abs(),
+            Func::Arg => value.arg(),
+            Func::Conj => value.conj(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
 enum Expr {
     Var(String),
     Val(f64),
+    /// Imaginary number, such as `2i` or `i`.
+    Imag(f64),
+    /// Number followed by a unit, such as `3 km`.
+    Quantity {
+        value: f64,
+        unit: String,
+    },
     UnOp {
         op: UnOp,
         expr: Box<Expr>,
@@ -729,30 +2557,130 @@ enum Expr {
         op: BinOp,
         rhs: Box<Expr>,
     },
+    Call {
+        func: Func,
+        arg: Box<Expr>,
+    },
+    /// Outputs of Expr node text with several, unnamed ones are named by their position.
+    Tuple(Vec<(String, Expr)>),
 }
 
 impl Expr {
+    /// Evaluates with plain numbers, NaN if units don't match.
     fn eval(&self, bindings: &[String], args: &[f64]) -> f64 {
+        let args = args
+            .iter()
+            .map(|&value| Quantity::number(value))
+            .collect::<Vec<_>>();
+        self.eval_units(bindings, &args, Backend::Float)
+            .map_or(f64::NAN, |quantity| quantity.value.to_f64())
+    }
+
+    /// Evaluates in base units with `backend`, converting literals and checking that added values match.
+    ///
+    /// Bounds of interval inputs are carried through every operation.
+    fn eval_units(
+        &self,
+        bindings: &[String],
+        args: &[Quantity],
+        backend: Backend,
+    ) -> Result<Quantity, String> {
         let binding_index =
             |name: &str| bindings.iter().position(|binding| binding == name).unwrap();
 
-        match self {
-            Expr::Var(ref name) => args[binding_index(name)],
-            Expr::Val(value) => *value,
-            Expr::UnOp { op, ref expr } => match op {
-                UnOp::Pos => expr.eval(bindings, args),
-                UnOp::Neg => -expr.eval(bindings, args),
+        Ok(match self {
+            Expr::Var(ref name) => {
+                let arg = args[binding_index(name)];
+                Quantity {
+                    value: arg.value.convert(backend)?,
+                    imag: arg.imag.map(|imag| imag.convert(backend)).transpose()?,
+                    ..arg
+                }
+            }
+            Expr::Val(value) => Quantity {
+                value: Real::from_f64(*value, backend)?,
+                ..Quantity::number(*value)
             },
-            Expr::BinOp {
-                ref lhs,
-                op,
+            Expr::Imag(value) => Quantity {
+                value: Real::zero(backend),
+                imag: Some(Real::from_f64(*value, backend)?),
+                ..Quantity::number(0.0)
+            },
+            Expr::Quantity { value, ref unit } => {
+                let (scale, dim) =
+                    units::unit(unit).ok_or_else(|| format!("Unknown unit `{unit}`"))?;
+                Quantity {
+                    value: Real::from_f64(*value, backend)?.mul(Real::from_f64(scale, backend)?)?,
+                    imag: None,
+                    dim,
+                    bounds: None,
+                }
+            }
+            Expr::UnOp { op, ref expr } => {
+                let value = expr.eval_units(bindings, args, backend)?;
+                match op {
+                    UnOp::Pos => value,
+                    UnOp::Neg => value.neg()?,
+                }
+            }
+            Expr::BinOp {
+                ref lhs,
+                op,
                 ref rhs,
-            } => match op {
-                BinOp::Add => lhs.eval(bindings, args) + rhs.eval(bindings, args),
-                BinOp::Sub => lhs.eval(bindings, args) - rhs.eval(bindings, args),
-                BinOp::Mul => lhs.eval(bindings, args) * rhs.eval(bindings, args),
-                BinOp::Div => lhs.eval(bindings, args) / rhs.eval(bindings, args),
+            } => {
+                let lhs = lhs.eval_units(bindings, args, backend)?;
+                let rhs = rhs.eval_units(bindings, args, backend)?;
+                match op {
+                    BinOp::Add => lhs.add(rhs, 1.0)?,
+                    BinOp::Sub => lhs.add(rhs, -1.0)?,
+                    BinOp::Mul => lhs.mul(rhs)?,
+                    BinOp::Div => lhs.div(rhs)?,
+                }
+            }
+            Expr::Call { func, ref arg } => func.apply(arg.eval_units(bindings, args, backend)?)?,
+            Expr::Tuple(_) => return Err("Tuple has no single value".to_owned()),
+        })
+    }
+
+    /// Returns copy of the expression with variables replaced by their substitutions.
+    fn substitute(&self, substitutions: &HashMap<&str, Expr>) -> Expr {
+        match self {
+            Expr::Var(name) => substitutions
+                .get(name.as_str())
+                .cloned()
+                .unwrap_or_else(|| self.clone()),
+            Expr::Val(_) | Expr::Imag(_) | Expr::Quantity { .. } => self.clone(),
+            Expr::UnOp { op, expr } => Expr::UnOp {
+                op: *op,
+                expr: Box::new(expr.substitute(substitutions)),
+            },
+            Expr::BinOp { lhs, op, rhs } => Expr::BinOp {
+                lhs: Box::new(lhs.substitute(substitutions)),
+                op: *op,
+                rhs: Box::new(rhs.substitute(substitutions)),
             },
+            Expr::Call { func, arg } => Expr::Call {
+                func: *func,
+                arg: Box::new(arg.substitute(substitutions)),
+            },
+            Expr::Tuple(outputs) => Expr::Tuple(
+                outputs
+                    .iter()
+                    .map(|(name, expr)| (name.clone(), expr.substitute(substitutions)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Checks if the expression needs complex numbers, which plain `f64` arithmetic lacks.
+    fn is_complex(&self) -> bool {
+        match self {
+            Expr::Var(_) | Expr::Val(_) | Expr::Quantity { .. } => false,
+            Expr::Imag(_) => true,
+            Expr::UnOp { expr, .. } => expr.is_complex(),
+            Expr::BinOp { lhs, rhs, .. } => lhs.is_complex() || rhs.is_complex(),
+            Expr::Call { func, arg } => *func != Func::Abs || arg.is_complex(),
+            Expr::Tuple(outputs) => outputs.iter().any(|(_, expr)| expr.is_complex()),
         }
     }
 
@@ -763,14 +2691,35 @@ impl Expr {
                     bindings.push(name.clone());
                 }
             }
-            Expr::Val(_) => {}
-            Expr::UnOp { expr, .. } => {
+            Expr::Val(_) | Expr::Imag(_) | Expr::Quantity { .. } => {}
+            Expr::UnOp { expr, .. } | Expr::Call { arg: expr, .. } => {
                 expr.extend_bindings(bindings);
             }
             Expr::BinOp { lhs, rhs, .. } => {
                 lhs.extend_bindings(bindings);
                 rhs.extend_bindings(bindings);
             }
+            Expr::Tuple(outputs) => {
+                for (_, expr) in outputs {
+                    expr.extend_bindings(bindings);
+                }
+            }
+        }
+    }
+
+    /// Expressions of the outputs, one for each tuple element.
+    fn outputs(&self) -> Vec<&Expr> {
+        match self {
+            Expr::Tuple(outputs) => outputs.iter().map(|(_, expr)| expr).collect(),
+            _ => vec![self],
+        }
+    }
+
+    /// Names of the outputs, unnamed ones are named by their position.
+    fn output_names(&self) -> Vec<String> {
+        match self {
+            Expr::Tuple(outputs) => outputs.iter().map(|(name, _)| name.clone()).collect(),
+            _ => vec!["0".to_owned()],
         }
     }
 }
@@ -820,30 +2769,19 @@ impl syn::parse::Parse for Expr {
             let content;
             syn::parenthesized!(content in input);
             let expr = content.parse::<Expr>()?;
-            if input.is_empty() {
+            if Self::at_end(input) {
                 return Ok(expr);
             }
             lhs = expr;
-        // } else if lookahead.peek(syn::LitFloat) {
-        //     let lit = input.parse::<syn::LitFloat>()?;
-        //     let value = lit.base10_parse::<f64>()?;
-        //     let expr = Expr::Val(value);
-        //     if input.is_empty() {
-        //         return Ok(expr);
-        //     }
-        //     lhs = expr;
-        } else if lookahead.peek(syn::LitInt) {
-            let lit = input.parse::<syn::LitInt>()?;
-            let value = lit.base10_parse::<f64>()?;
-            let expr = Expr::Val(value);
-            if input.is_empty() {
+        } else if lookahead.peek(syn::LitFloat) || lookahead.peek(syn::LitInt) {
+            let expr = Self::parse_number(input)?;
+            if Self::at_end(input) {
                 return Ok(expr);
             }
             lhs = expr;
         } else if lookahead.peek(syn::Ident) {
-            let ident = input.parse::<syn::Ident>()?;
-            let expr = Expr::Var(ident.to_string());
-            if input.is_empty() {
+            let expr = Self::parse_ident(input)?;
+            if Self::at_end(input) {
                 return Ok(expr);
             }
             lhs = expr;
@@ -860,6 +2798,97 @@ impl syn::parse::Parse for Expr {
 }
 
 impl Expr {
+    /// Checks if the expression ends here, at the end of a statement or tuple element.
+    fn at_end(input: syn::parse::ParseStream) -> bool {
+        input.is_empty() || input.peek(syn::Token![;]) || input.peek(syn::Token![,])
+    }
+
+    /// Parses Expr node text of statements separated by `;`.
+    ///
+    /// `let name = ...` binds a name for the statements after it, `name = ...` adds a named output
+    /// that can also be used after it, and other expressions add an unnamed output, or one for each
+    /// element of a tuple such as `(a + b, a - b)`. Bound names are substituted into the outputs.
+    fn parse_block(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut names = HashMap::<String, Expr>::new();
+        let mut outputs = Vec::<(String, Expr)>::new();
+        let substitute = |names: &HashMap<String, Expr>, expr: Expr| {
+            let substitutions = names
+                .iter()
+                .map(|(name, expr)| (name.as_str(), expr.clone()))
+                .collect();
+            expr.substitute(&substitutions)
+        };
+
+        loop {
+            if input.peek(syn::Token![let]) {
+                input.parse::<syn::Token![let]>()?;
+                let name = Self::parse_binding(input)?.to_string();
+                input.parse::<syn::Token![=]>()?;
+                let expr = substitute(&names, input.parse::<Expr>()?);
+                names.insert(name, expr);
+            } else if input.peek(syn::Ident) && input.peek2(syn::Token![=]) {
+                let ident = Self::parse_binding(input)?;
+                let name = ident.to_string();
+                if outputs.iter().any(|(output, _)| *output == name) {
+                    let message = format!("Output `{name}` is defined twice");
+                    return Err(syn::Error::new(ident.span(), message));
+                }
+                input.parse::<syn::Token![=]>()?;
+                let expr = substitute(&names, input.parse::<Expr>()?);
+                names.insert(name.clone(), expr.clone());
+                outputs.push((name, expr));
+            } else if Self::peek_tuple(input) {
+                let content;
+                syn::parenthesized!(content in input);
+                let elements =
+                    syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated(
+                        &content,
+                    )?;
+                outputs.extend(
+                    elements
+                        .into_iter()
+                        .map(|expr| (String::new(), substitute(&names, expr))),
+                );
+            } else {
+                let expr = substitute(&names, input.parse::<Expr>()?);
+                outputs.push((String::new(), expr));
+            }
+
+            if input.is_empty() {
+                break;
+            }
+            input.parse::<syn::Token![;]>()?;
+            if input.is_empty() {
+                break;
+            }
+        }
+
+        match outputs.len() {
+            0 => Err(input.error("Expected output after the let bindings")),
+            1 if outputs[0].0.is_empty() => Ok(outputs.remove(0).1),
+            _ => {
+                for (idx, (name, _)) in outputs.iter_mut().enumerate() {
+                    if name.is_empty() {
+                        *name = idx.to_string();
+                    }
+                }
+                Ok(Expr::Tuple(outputs))
+            }
+        }
+    }
+
+    /// Checks if a tuple statement follows, parentheses with a comma after the first element.
+    fn peek_tuple(input: syn::parse::ParseStream) -> bool {
+        let fork = input.fork();
+        let tuple = || -> syn::Result<bool> {
+            let content;
+            syn::parenthesized!(content in fork);
+            content.parse::<Expr>()?;
+            Ok(content.peek(syn::Token![,]) && (fork.is_empty() || fork.peek(syn::Token![;])))
+        };
+        input.peek(syn::token::Paren) && tuple().unwrap_or(false)
+    }
+
     fn parse_with_unop(op: UnOp, input: syn::parse::ParseStream) -> syn::Result<Self> {
         let lookahead = input.lookahead1();
 
@@ -871,39 +2900,34 @@ impl Expr {
                 op,
                 expr: Box::new(content.parse::<Expr>()?),
             };
-            if input.is_empty() {
+            if Self::at_end(input) {
                 return Ok(expr);
             }
             lhs = expr;
-        } else if lookahead.peek(syn::LitFloat) {
-            let lit = input.parse::<syn::LitFloat>()?;
-            let value = lit.base10_parse::<f64>()?;
-            let expr = Expr::UnOp {
-                op,
-                expr: Box::new(Expr::Val(value)),
+        } else if lookahead.peek(syn::LitFloat) || lookahead.peek(syn::LitInt) {
+            // Negated numbers are literals of their own, which print back the same.
+            let expr = match (op, Self::parse_number(input)?) {
+                (UnOp::Neg, Expr::Val(value)) => Expr::Val(-value),
+                (UnOp::Neg, Expr::Imag(value)) => Expr::Imag(-value),
+                (UnOp::Neg, Expr::Quantity { value, unit }) => Expr::Quantity {
+                    value: -value,
+                    unit,
+                },
+                (op, number) => Expr::UnOp {
+                    op,
+                    expr: Box::new(number),
+                },
             };
-            if input.is_empty() {
-                return Ok(expr);
-            }
-            lhs = expr;
-        } else if lookahead.peek(syn::LitInt) {
-            let lit = input.parse::<syn::LitInt>()?;
-            let value = lit.base10_parse::<f64>()?;
-            let expr = Expr::UnOp {
-                op,
-                expr: Box::new(Expr::Val(value)),
-            };
-            if input.is_empty() {
+            if Self::at_end(input) {
                 return Ok(expr);
             }
             lhs = expr;
         } else if lookahead.peek(syn::Ident) {
-            let ident = input.parse::<syn::Ident>()?;
             let expr = Expr::UnOp {
                 op,
-                expr: Box::new(Expr::Var(ident.to_string())),
+                expr: Box::new(Self::parse_ident(input)?),
             };
-            if input.is_empty() {
+            if Self::at_end(input) {
                 return Ok(expr);
             }
             lhs = expr;
@@ -916,58 +2940,717 @@ impl Expr {
         Self::parse_binop(Box::new(lhs), op, input)
     }
 
+    /// Parses the name of a `let` binding or a named output, which can't be the imaginary unit `i`.
+    fn parse_binding(input: syn::parse::ParseStream) -> syn::Result<syn::Ident> {
+        let ident = input.parse::<syn::Ident>()?;
+        if ident == "i" {
+            let message = "`i` is the imaginary unit and can't be a binding name";
+            return Err(syn::Error::new(ident.span(), message));
+        }
+        Ok(ident)
+    }
+
+    /// Parses a variable, the imaginary unit `i` or a function call such as `abs(x)`.
+    fn parse_ident(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let ident = input.parse::<syn::Ident>()?;
+        let name = ident.to_string();
+        if input.peek(syn::token::Paren) {
+            let func = Func::from_name(&name).ok_or_else(|| {
+                syn::Error::new(ident.span(), format!("Unknown function `{name}`"))
+            })?;
+            let content;
+            syn::parenthesized!(content in input);
+            return Ok(Expr::Call {
+                func,
+                arg: Box::new(content.parse::<Expr>()?),
+            });
+        }
+        Ok(if name == "i" {
+            Expr::Imag(1.0)
+        } else {
+            Expr::Var(name)
+        })
+    }
+
+    /// Parses a number with an optional unit, written either after it as in `3 km`
+    /// or as its suffix as in `3km`. The unit `i` makes an imaginary number such as `2i`.
+    ///
+    /// Numbers too large for `f64` are rejected, so that literals are always finite.
+    fn parse_number(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let (value, suffix, span) = if input.peek(syn::LitFloat) {
+            let lit = input.parse::<syn::LitFloat>()?;
+            (
+                lit.base10_parse::<f64>()?,
+                lit.suffix().to_owned(),
+                lit.span(),
+            )
+        } else {
+            let lit = input.parse::<syn::LitInt>()?;
+            (
+                lit.base10_parse::<f64>()?,
+                lit.suffix().to_owned(),
+                lit.span(),
+            )
+        };
+
+        if !value.is_finite() {
+            return Err(syn::Error::new(span, "Number is too large"));
+        }
+
+        let (unit, span) = if !suffix.is_empty() {
+            (suffix, span)
+        } else if input.peek(syn::Ident) {
+            let ident = input.parse::<syn::Ident>()?;
+            (ident.to_string(), ident.span())
+        } else {
+            return Ok(Expr::Val(value));
+        };
+
+        if unit == "i" {
+            return Ok(Expr::Imag(value));
+        }
+        if units::unit(&unit).is_none() {
+            return Err(syn::Error::new(span, format!("Unknown unit `{unit}`")));
+        }
+        Ok(Expr::Quantity { value, unit })
+    }
+
     fn parse_binop(lhs: Box<Expr>, op: BinOp, input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut rhs = Box::new(Self::parse_operand(input)?);
+
+        while !Self::at_end(input) {
+            let next_op = input.parse::<BinOp>()?;
+
+            if next_op.precedence() > op.precedence() {
+                // Tighter operator takes the operand parsed so far.
+                rhs = Box::new(Self::BinOp {
+                    lhs: rhs,
+                    op: next_op,
+                    rhs: Box::new(Self::parse_operand(input)?),
+                });
+            } else {
+                let lhs = Self::BinOp { lhs, op, rhs };
+                return Self::parse_binop(Box::new(lhs), next_op, input);
+            }
+        }
+
+        Ok(Self::BinOp { lhs, op, rhs })
+    }
+
+    fn parse_operand(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let lookahead = input.lookahead1();
 
-        let rhs;
         if lookahead.peek(syn::token::Paren) {
             let content;
             syn::parenthesized!(content in input);
-            rhs = Box::new(content.parse::<Expr>()?);
-            if input.is_empty() {
-                return Ok(Expr::BinOp { lhs, op, rhs });
+            content.parse::<Expr>()
+        } else if lookahead.peek(syn::LitFloat) || lookahead.peek(syn::LitInt) {
+            Self::parse_number(input)
+        } else if lookahead.peek(syn::Ident) {
+            Self::parse_ident(input)
+        } else {
+            Err(lookahead.error())
+        }
+    }
+}
+
+impl UnOp {
+    const fn symbol(self) -> &'static str {
+        match self {
+            UnOp::Pos => "+",
+            UnOp::Neg => "-",
+        }
+    }
+}
+
+impl BinOp {
+    const fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+        }
+    }
+
+    const fn math_symbol(self) -> &'static str {
+        match self {
+            BinOp::Mul => "·",
+            _ => self.symbol(),
+        }
+    }
+
+    const fn latex_symbol(self) -> &'static str {
+        match self {
+            BinOp::Mul => r"\cdot",
+            _ => self.symbol(),
+        }
+    }
+
+    /// Binding strength of the operator, higher binds tighter.
+    const fn precedence(self) -> u8 {
+        match self {
+            BinOp::Add | BinOp::Sub => 1,
+            BinOp::Mul | BinOp::Div => 2,
+        }
+    }
+}
+
+/// Precedence of unary operators and negative literals.
+const UNARY_PRECEDENCE: u8 = 3;
+
+/// Precedence of variables and non-negative literals.
+const ATOM_PRECEDENCE: u8 = 4;
+
+impl Expr {
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Val(value) | Expr::Imag(value) | Expr::Quantity { value, .. }
+                if value.is_sign_negative() =>
+            {
+                UNARY_PRECEDENCE
             }
-        } else if lookahead.peek(syn::LitFloat) {
-            let lit = input.parse::<syn::LitFloat>()?;
-            let value = lit.base10_parse::<f64>()?;
-            rhs = Box::new(Expr::Val(value));
-            if input.is_empty() {
-                return Ok(Expr::BinOp { lhs, op, rhs });
+            Expr::Var(_)
+            | Expr::Val(_)
+            | Expr::Imag(_)
+            | Expr::Quantity { .. }
+            | Expr::Call { .. }
+            | Expr::Tuple(_) => ATOM_PRECEDENCE,
+            Expr::UnOp { .. } => UNARY_PRECEDENCE,
+            Expr::BinOp { op, .. } => op.precedence(),
+        }
+    }
+
+    /// Checks if `operand` of `op` must be parenthesized in text form.
+    ///
+    /// The parser folds operators of equal precedence to the left
+    /// and accepts unary operators only at the start of an expression.
+    fn text_parens(op: BinOp, operand: &Expr, rhs: bool, leading: bool) -> bool {
+        let precedence = operand.precedence();
+        if precedence == UNARY_PRECEDENCE {
+            !leading
+        } else if rhs {
+            precedence <= op.precedence()
+        } else {
+            precedence < op.precedence()
+        }
+    }
+
+    /// Checks if `operand` of `op` must be parenthesized in math form.
+    ///
+    /// Fractions group their operands visually, so they never need parentheses.
+    fn math_parens(op: BinOp, operand: &Expr, rhs: bool) -> bool {
+        let precedence = match operand {
+            Expr::BinOp { op: BinOp::Div, .. } => ATOM_PRECEDENCE,
+            _ => operand.precedence(),
+        };
+        match op {
+            BinOp::Div => false,
+            _ if precedence == UNARY_PRECEDENCE => rhs,
+            BinOp::Sub if rhs => precedence <= op.precedence(),
+            _ => precedence < op.precedence(),
+        }
+    }
+
+    fn write_text(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        leading: bool,
+        syntax: Syntax,
+    ) -> std::fmt::Result {
+        match self {
+            Expr::Var(name) => f.write_str(name),
+            Expr::Val(value) => match syntax {
+                Syntax::Expr => write_number(f, *value),
+                Syntax::Rust if value.is_nan() => f.write_str("f64::NAN"),
+                Syntax::Rust if value.is_infinite() => f.write_str(if *value > 0.0 {
+                    "f64::INFINITY"
+                } else {
+                    "f64::NEG_INFINITY"
+                }),
+                Syntax::Rust => write!(f, "{value:?}"),
+            },
+            Expr::Imag(value) if *value == 1.0 => f.write_str("i"),
+            Expr::Imag(value) => {
+                write_number(f, *value)?;
+                f.write_str("i")
             }
-        } else if lookahead.peek(syn::LitInt) {
-            let lit = input.parse::<syn::LitInt>()?;
-            let value = lit.base10_parse::<f64>()?;
-            rhs = Box::new(Expr::Val(value));
-            if input.is_empty() {
-                return Ok(Expr::BinOp { lhs, op, rhs });
+            Expr::Quantity { value, unit } => match syntax {
+                Syntax::Expr => {
+                    write_number(f, *value)?;
+                    write!(f, " {unit}")
+                }
+                // Generated code works in base units.
+                Syntax::Rust => {
+                    let scale = units::unit(unit).map_or(1.0, |(scale, _)| scale);
+                    Expr::Val(value * scale).write_text(f, leading, syntax)
+                }
+            },
+            Expr::UnOp { op, expr } => {
+                f.write_str(op.symbol())?;
+                // Otherwise `-2` would read back as a negative literal.
+                let literal = match **expr {
+                    Expr::Imag(value) => value != 1.0,
+                    Expr::Val(_) | Expr::Quantity { .. } => true,
+                    _ => false,
+                };
+                if expr.precedence() < ATOM_PRECEDENCE || (*op == UnOp::Neg && literal) {
+                    f.write_str("(")?;
+                    expr.write_text(f, true, syntax)?;
+                    f.write_str(")")
+                } else {
+                    expr.write_text(f, false, syntax)
+                }
             }
-        } else if lookahead.peek(syn::Ident) {
-            let ident = input.parse::<syn::Ident>()?;
-            rhs = Box::new(Expr::Var(ident.to_string()));
-            if input.is_empty() {
-                return Ok(Expr::BinOp { lhs, op, rhs });
+            Expr::BinOp { lhs, op, rhs } => {
+                if Self::text_parens(*op, lhs, false, leading) {
+                    f.write_str("(")?;
+                    lhs.write_text(f, true, syntax)?;
+                    f.write_str(")")?;
+                } else {
+                    lhs.write_text(f, leading, syntax)?;
+                }
+                write!(f, " {} ", op.symbol())?;
+                if Self::text_parens(*op, rhs, true, false) {
+                    f.write_str("(")?;
+                    rhs.write_text(f, true, syntax)?;
+                    f.write_str(")")
+                } else {
+                    rhs.write_text(f, false, syntax)
+                }
+            }
+            Expr::Call { func, arg } => {
+                match (syntax, func) {
+                    (Syntax::Rust, Func::Abs) => f.write_str("f64::abs(")?,
+                    _ => write!(f, "{}(", func.name())?,
+                }
+                arg.write_text(f, true, syntax)?;
+                f.write_str(")")
+            }
+            Expr::Tuple(outputs)
+                if outputs
+                    .iter()
+                    .enumerate()
+                    .all(|(idx, (name, _))| *name == idx.to_string()) =>
+            {
+                f.write_str("(")?;
+                for (idx, (_, expr)) in outputs.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(", ")?;
+                    }
+                    expr.write_text(f, true, syntax)?;
+                }
+                f.write_str(")")
+            }
+            Expr::Tuple(outputs) => {
+                for (idx, (name, expr)) in outputs.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str("; ")?;
+                    }
+                    if *name != idx.to_string() {
+                        write!(f, "{name} = ")?;
+                    }
+                    expr.write_text(f, true, syntax)?;
+                }
+                Ok(())
             }
-        } else {
-            return Err(lookahead.error());
         }
+    }
 
-        let next_op = input.parse::<BinOp>()?;
+    /// Renders the expression as LaTeX math.
+    fn latex(&self) -> String {
+        let mut out = String::new();
+        self.write_latex(&mut out);
+        out
+    }
+
+    fn write_latex(&self, out: &mut String) {
+        use std::fmt::Write as _;
+
+        let write_operand = |out: &mut String, operand: &Expr, parens: bool| {
+            if parens {
+                out.push_str(r"\left(");
+                operand.write_latex(out);
+                out.push_str(r"\right)");
+            } else {
+                operand.write_latex(out);
+            }
+        };
 
-        if let (BinOp::Add | BinOp::Sub, BinOp::Mul | BinOp::Div) = (op, next_op) {
-            let rhs = Self::parse_binop(rhs, next_op, input)?;
-            Ok(Self::BinOp {
+        match self {
+            Expr::Var(name) if name.chars().count() == 1 => out.push_str(name),
+            Expr::Var(name) => {
+                let _ = write!(out, r"\mathit{{{}}}", name.replace('_', r"\_"));
+            }
+            Expr::Val(value) => {
+                let _ = write!(out, "{value}");
+            }
+            Expr::Imag(value) if *value == 1.0 => out.push('i'),
+            Expr::Imag(value) => {
+                let _ = write!(out, "{value}i");
+            }
+            Expr::Quantity { value, unit } => {
+                let _ = write!(out, r"{value}\,\mathrm{{{unit}}}");
+            }
+            Expr::UnOp { op, expr } => {
+                out.push_str(op.symbol());
+                write_operand(out, expr, expr.precedence() < ATOM_PRECEDENCE);
+            }
+            Expr::BinOp {
                 lhs,
-                op,
-                rhs: Box::new(rhs),
-            })
-        } else {
-            let lhs = Self::BinOp { lhs, op, rhs };
-            Self::parse_binop(Box::new(lhs), next_op, input)
+                op: BinOp::Div,
+                rhs,
+            } => {
+                out.push_str(r"\frac{");
+                lhs.write_latex(out);
+                out.push_str("}{");
+                rhs.write_latex(out);
+                out.push('}');
+            }
+            Expr::BinOp { lhs, op, rhs } => {
+                write_operand(out, lhs, Self::math_parens(*op, lhs, false));
+                let _ = write!(out, " {} ", op.latex_symbol());
+                write_operand(out, rhs, Self::math_parens(*op, rhs, true));
+            }
+            Expr::Call {
+                func: Func::Abs,
+                arg,
+            } => {
+                out.push_str(r"\left|");
+                arg.write_latex(out);
+                out.push_str(r"\right|");
+            }
+            Expr::Call {
+                func: Func::Conj,
+                arg,
+            } => {
+                out.push_str(r"\overline{");
+                arg.write_latex(out);
+                out.push('}');
+            }
+            Expr::Call { func, arg } => {
+                out.push_str(match func {
+                    Func::Re => r"\operatorname{Re}",
+                    Func::Im => r"\operatorname{Im}",
+                    _ => r"\arg",
+                });
+                write_operand(out, arg, true);
+            }
+            Expr::Tuple(outputs) => {
+                for (idx, (name, expr)) in outputs.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(r",\quad ");
+                    }
+                    if *name != idx.to_string() {
+                        Expr::Var(name.clone()).write_latex(out);
+                        out.push_str(" = ");
+                    }
+                    expr.write_latex(out);
+                }
+            }
         }
     }
+
+    /// Renders the expression as typeset math, with stacked fractions.
+    fn show_math(&self, ui: &mut Ui) {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 2.0;
+            self.show_math_inner(ui);
+        });
+    }
+
+    fn show_math_inner(&self, ui: &mut Ui) {
+        let show_operand = |ui: &mut Ui, operand: &Expr, parens: bool| {
+            if parens {
+                ui.label("(");
+                operand.show_math_inner(ui);
+                ui.label(")");
+            } else {
+                operand.show_math_inner(ui);
+            }
+        };
+
+        match self {
+            Expr::Var(name) => {
+                ui.label(egui::RichText::new(name).italics());
+            }
+            Expr::Val(value) => {
+                ui.label(format!("{value}"));
+            }
+            Expr::Imag(value) if *value == 1.0 => {
+                ui.label("i");
+            }
+            Expr::Imag(value) => {
+                ui.label(format!("{value}i"));
+            }
+            Expr::Quantity { value, unit } => {
+                ui.label(format!("{value} {unit}"));
+            }
+            Expr::UnOp { op, expr } => {
+                ui.label(op.symbol());
+                show_operand(ui, expr, expr.precedence() < ATOM_PRECEDENCE);
+            }
+            Expr::BinOp {
+                lhs,
+                op: BinOp::Div,
+                rhs,
+            } => {
+                ui.vertical(|ui| {
+                    ui.spacing_mut().item_spacing.y = 2.0;
+                    let numerator = ui.horizontal(|ui| lhs.show_math_inner(ui)).response.rect;
+                    let (bar, _) =
+                        ui.allocate_exact_size(Vec2::new(0.0, 1.0), egui::Sense::hover());
+                    let denominator = ui.horizontal(|ui| rhs.show_math_inner(ui)).response.rect;
+
+                    let width = numerator.width().max(denominator.width());
+                    ui.painter().hline(
+                        bar.left()..=bar.left() + width,
+                        bar.center().y,
+                        Stroke::new(1.0, ui.visuals().text_color()),
+                    );
+                });
+            }
+            Expr::BinOp { lhs, op, rhs } => {
+                show_operand(ui, lhs, Self::math_parens(*op, lhs, false));
+                ui.label(op.math_symbol());
+                show_operand(ui, rhs, Self::math_parens(*op, rhs, true));
+            }
+            Expr::Call {
+                func: Func::Abs,
+                arg,
+            } => {
+                ui.label("|");
+                arg.show_math_inner(ui);
+                ui.label("|");
+            }
+            Expr::Call { func, arg } => {
+                ui.label(func.name());
+                show_operand(ui, arg, true);
+            }
+            Expr::Tuple(outputs) => {
+                ui.vertical(|ui| {
+                    for (idx, (name, expr)) in outputs.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if *name != idx.to_string() {
+                                ui.label(egui::RichText::new(name).italics());
+                                ui.label("=");
+                            }
+                            expr.show_math_inner(ui);
+                        });
+                    }
+                });
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Expr {
+    /// Writes the expression in canonical form with minimal parentheses,
+    /// such that parsing the output yields the same expression.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.write_text(f, true, Syntax::Expr)
+    }
+}
+
+/// Writes a number the parser reads back as the same value,
+/// with an exponent if it is very large or small.
+fn write_number(f: &mut std::fmt::Formatter<'_>, value: f64) -> std::fmt::Result {
+    let magnitude = value.abs();
+    if magnitude != 0.0 && !(1e-6..1e16).contains(&magnitude) {
+        write!(f, "{value:e}")
+    } else {
+        write!(f, "{value}")
+    }
+}
+
+/// Syntax written by [`Expr::write_text`].
+#[derive(Clone, Copy)]
+enum Syntax {
+    /// Expression language accepted by the parser.
+    Expr,
+    /// Rust `f64` arithmetic.
+    Rust,
+}
+
+/// Displays an expression as Rust `f64` arithmetic.
+struct RustExpr<'a>(&'a Expr);
+
+impl std::fmt::Display for RustExpr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.write_text(f, true, Syntax::Rust)
+    }
+}
+
+/// Formats `v` rounded to `precision` decimals, or in full without one.
+fn format_float(v: f64, precision: Option<usize>) -> String {
+    let Some(precision) = precision else {
+        return format!("{v}");
+    };
+    let scale = 10f64.powi(i32::try_from(precision).unwrap_or(i32::MAX));
+    let rounded = (v * scale).round() / scale;
+    // Scaling overflows for the largest values, which have no decimals to round anyway.
+    if rounded.is_finite() {
+        format!("{rounded}")
+    } else {
+        format!("{v}")
+    }
 }
 
-fn format_float(v: f64) -> String {
-    let v = (v * 1000.0).round() / 1000.0;
-    format!("{v}")
+#[cfg(test)]
+mod tests {
+    use syn::parse::Parser as _;
+
+    use super::{units, BinOp, Expr, Func, ListNode, ListOp, Nodes, UnOp, Value};
+
+    fn parse(text: &str) -> Expr {
+        Expr::parse_block
+            .parse_str(text)
+            .unwrap_or_else(|err| panic!("`{text}`: {err}"))
+    }
+
+    fn var(name: &str) -> Box<Expr> {
+        Box::new(Expr::Var(name.to_owned()))
+    }
+
+    fn bin(lhs: Box<Expr>, op: BinOp, rhs: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::BinOp { lhs, op, rhs })
+    }
+
+    fn neg(expr: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::UnOp {
+            op: UnOp::Neg,
+            expr,
+        })
+    }
+
+    #[track_caller]
+    fn assert_round_trip(expr: &Expr) {
+        let text = expr.to_string();
+        assert_eq!(parse(&text), *expr, "printed as `{text}`");
+    }
+
+    #[test]
+    fn parsed_text_round_trips() {
+        for text in [
+            "1 + 2 * 3",
+            "(1 + 2) * 3",
+            "a - (b - c)",
+            "a - b - c",
+            "a / (b * c)",
+            "a / b / c",
+            "-x * y",
+            "x * (-y)",
+            "-(x + y)",
+            "-(-x)",
+            "-2 + x",
+            "x - (-2)",
+            "-(2)",
+            "+2",
+            "3 km + 200 m",
+            "-3 km",
+            "2 + 3i",
+            "-i",
+            "abs(x - 1) + conj(2i)",
+            "1e300 * x",
+            "0.0000001 + x",
+            "(a + b, a - b)",
+            "sum = a + b; diff = a - b",
+            "let d = a - b; d * d",
+        ] {
+            assert_round_trip(&parse(text));
+        }
+    }
+
+    #[test]
+    fn built_expressions_round_trip() {
+        let exprs = [
+            Expr::Val(-2.0),
+            *neg(Box::new(Expr::Val(2.0))),
+            *neg(Box::new(Expr::Val(-2.0))),
+            *neg(neg(var("x"))),
+            *bin(Box::new(Expr::Val(-2.0)), BinOp::Mul, var("x")),
+            *bin(var("x"), BinOp::Sub, Box::new(Expr::Val(-2.0))),
+            *bin(var("a"), BinOp::Sub, bin(var("b"), BinOp::Add, var("c"))),
+            *bin(bin(var("a"), BinOp::Add, var("b")), BinOp::Div, var("c")),
+            *bin(var("a"), BinOp::Div, bin(var("b"), BinOp::Div, var("c"))),
+            *bin(neg(var("a")), BinOp::Mul, neg(var("b"))),
+            Expr::Val(1e300),
+            Expr::Val(-1.5e-9),
+            Expr::Val(f64::MAX),
+            Expr::Val(f64::MIN_POSITIVE),
+            Expr::Imag(-1.0),
+            Expr::Imag(2.5e20),
+            Expr::Quantity {
+                value: -4e-8,
+                unit: "m".to_owned(),
+            },
+            Expr::Call {
+                func: Func::Abs,
+                arg: neg(var("x")),
+            },
+        ];
+        for expr in &exprs {
+            assert_round_trip(expr);
+        }
+    }
+
+    #[test]
+    fn negated_numbers_are_literals() {
+        assert_eq!(parse("-2"), Expr::Val(-2.0));
+        assert_eq!(parse("-(2)"), *neg(Box::new(Expr::Val(2.0))));
+        assert_eq!(Expr::Val(-2.0).to_string(), "-2");
+        assert_eq!(neg(Box::new(Expr::Val(2.0))).to_string(), "-(2)");
+    }
+
+    #[test]
+    fn rejects_numbers_too_large() {
+        assert!(Expr::parse_block.parse_str("1e999").is_err());
+        assert!(Expr::parse_block.parse_str("-1e400 * x").is_err());
+    }
+
+    #[test]
+    fn rejects_imaginary_unit_bindings() {
+        for text in ["let i = 2; x * i", "i = 2", "a = x; i = 2"] {
+            let err = Expr::parse_block.parse_str(text).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "`i` is the imaginary unit and can't be a binding name"
+            );
+        }
+        assert_eq!(
+            parse("x * i"),
+            *bin(var("x"), BinOp::Mul, Box::new(Expr::Imag(1.0)))
+        );
+
+        let mut map = ListNode::new(ListOp::Map);
+        map.inputs[1] = Value::String("x * i".to_owned());
+        assert!(map.expr().is_err());
+        map.inputs[1] = Value::String("x * idx".to_owned());
+        assert!(map.expr().is_ok());
+    }
+
+    #[test]
+    fn migrates_list_index_binding() {
+        let mut map = ListNode::new(ListOp::Map);
+        map.inputs[1] = Value::String("x * i + i_max / 2i".to_owned());
+        let saved = serde_json::to_string(&map).unwrap();
+        let loaded = serde_json::from_str::<ListNode>(&saved).unwrap();
+        assert_eq!(loaded.inputs[1].string(), "x * idx + i_max / 2i");
+    }
+
+    #[test]
+    fn numbers_load_with_units() {
+        let Ok(Nodes::Number(plain)) = serde_json::from_str(r#"{"Number": 2.5}"#) else {
+            panic!("Number saved without unit doesn't load");
+        };
+        assert_eq!((plain.value, plain.unit.as_str()), (2.5, ""));
+
+        let text = r#"{"Number": {"value": 3.0, "unit": "km"}}"#;
+        let Ok(node @ Nodes::Number(_)) = serde_json::from_str::<Nodes>(text) else {
+            panic!("Number with unit doesn't load");
+        };
+        let quantity = node.quantity_out(0);
+        assert_eq!(quantity.value.to_f64(), 3000.0);
+        assert_eq!(Some(quantity.dim), units::unit("m").map(|(_, dim)| dim));
+    }
 }
@@ -0,0 +1,166 @@
+//! Complex numbers, and the nodes that build and split them.
+
+use super::{numeric::Real, units::Quantity, PinCompat, Value};
+
+/// Number with real and imaginary parts in the same backend.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Complex {
+    pub re: Real,
+    pub im: Real,
+}
+
+impl Complex {
+    pub fn conj(self) -> Result<Complex, String> {
+        Ok(Complex {
+            re: self.re,
+            im: self.im.neg()?,
+        })
+    }
+
+    pub fn add(self, other: Complex) -> Result<Complex, String> {
+        Ok(Complex {
+            re: self.re.add(other.re)?,
+            im: self.im.add(other.im)?,
+        })
+    }
+
+    pub fn mul(self, other: Complex) -> Result<Complex, String> {
+        let Complex { re: a, im: b } = self;
+        let Complex { re: c, im: d } = other;
+        Ok(Complex {
+            re: a.mul(c)?.add(b.mul(d)?.neg()?)?,
+            im: a.mul(d)?.add(b.mul(c)?)?,
+        })
+    }
+
+    pub fn div(self, other: Complex) -> Result<Complex, String> {
+        let Complex { re: c, im: d } = other;
+        let norm = c.mul(c)?.add(d.mul(d)?)?;
+        let product = self.mul(other.conj()?)?;
+        Ok(Complex {
+            re: product.re.div(norm)?,
+            im: product.im.div(norm)?,
+        })
+    }
+
+    /// Distance from zero, rounded for the exact backends.
+    pub fn abs(self) -> Result<Real, String> {
+        Real::from_f64(self.re.to_f64().hypot(self.im.to_f64()), self.re.backend())
+    }
+
+    /// Angle from the positive real axis in radians, from -π to π.
+    pub fn arg(self) -> Result<Real, String> {
+        Real::from_f64(self.im.to_f64().atan2(self.re.to_f64()), self.re.backend())
+    }
+}
+
+impl std::fmt::Display for Complex {
+    /// Writes `a + bi`, with fractions of the imaginary part in parentheses,
+    /// and both parts rounded to the formatter's precision.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (sign, im) = if self.im.to_f64().is_sign_negative() {
+            ("-", self.im.neg().unwrap_or(self.im))
+        } else {
+            ("+", self.im)
+        };
+        let im = match f.precision() {
+            Some(precision) => format!("{im:.precision$}"),
+            None => im.to_string(),
+        };
+        std::fmt::Display::fmt(&self.re, f)?;
+        if im.contains('/') {
+            write!(f, " {sign} ({im})i")
+        } else {
+            write!(f, " {sign} {im}i")
+        }
+    }
+}
+
+node_ops! {
+    pub enum ComplexOp {
+        Compose as "ComposeComplex": "Compose Complex",
+            "Builds complex number from its real and imaginary parts";
+        Decompose as "DecomposeComplex": "Decompose Complex",
+            "Outputs real and imaginary parts, magnitude and angle of a complex number";
+    }
+}
+
+impl ComplexOp {
+    /// Labels and initial values of the inputs.
+    fn inputs(self) -> Vec<(&'static str, Value)> {
+        match self {
+            ComplexOp::Compose => vec![("Re", Value::Number(0.0)), ("Im", Value::Number(0.0))],
+            ComplexOp::Decompose => vec![("Complex", Value::Number(0.0))],
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ComplexNode {
+    pub(super) op: ComplexOp,
+    /// Real and imaginary parts when composing, the complex number when decomposing.
+    pub(super) inputs: Vec<Value>,
+    /// Complex value of the connected input when decomposing, copied from the remote.
+    #[serde(skip)]
+    pub(super) wired: Option<Quantity>,
+}
+
+impl ComplexNode {
+    pub fn new(op: ComplexOp) -> Self {
+        ComplexNode {
+            op,
+            inputs: op.inputs().into_iter().map(|(_, value)| value).collect(),
+            wired: None,
+        }
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        self.op.inputs()[idx].0
+    }
+
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        self.inputs[idx].compat()
+    }
+
+    pub(super) const fn outputs(&self) -> usize {
+        match self.op {
+            ComplexOp::Compose => 1,
+            ComplexOp::Decompose => 4,
+        }
+    }
+
+    pub(super) fn label_out(&self, idx: usize) -> &str {
+        match self.op {
+            ComplexOp::Compose => "",
+            ComplexOp::Decompose => ["Re", "Im", "Abs", "Arg"][idx],
+        }
+    }
+
+    /// Output with its unit, the decomposed parts keep the unit of the input.
+    pub(super) fn quantity_out(&self, idx: usize) -> Result<Quantity, String> {
+        match self.op {
+            ComplexOp::Compose => Ok(Quantity {
+                imag: Some(Real::Float(self.inputs[1].number())),
+                ..Quantity::number(self.inputs[0].number())
+            }),
+            ComplexOp::Decompose => {
+                let input = self
+                    .wired
+                    .unwrap_or(Quantity::number(self.inputs[0].number()));
+                match idx {
+                    0 => Ok(input.re()),
+                    1 => Ok(input.im()),
+                    2 => input.abs(),
+                    _ => input.arg(),
+                }
+            }
+        }
+    }
+
+    pub(super) fn value_out(&self, idx: usize) -> Value {
+        Value::Number(
+            self.quantity_out(idx)
+                .map_or(f64::NAN, |quantity| quantity.value.to_f64()),
+        )
+    }
+}
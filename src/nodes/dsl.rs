@@ -0,0 +1,1554 @@
+//! Text language describing graphs, to build them by typing and to diff them in review.
+//!
+//! ```text
+//! a = Number(3);
+//! b = Expr("a * x + 1", x = a, a = 2);
+//! Sink(b);
+//! ```
+//!
+//! Each statement creates one node, optionally named so that later statements can wire from it.
+//! Expr bindings are given either a node to wire from or a number.
+//! Bools are written as `true` and `false`, vectors as `(1, 2, 3)`, colors as `#rrggbbaa`
+//! and lists as `[1, 2, 3]`. Image inputs left unwired are written as `none`.
+//! Numbers also include `inf`, `-inf` and `nan`.
+//! Outputs other than the first are referred to as `node.output`, such as `regex.match`,
+//! or with the name quoted if it is not a valid name, such as `table."unit price"`.
+//! String manipulation nodes take their inputs in order, and Format placeholders may also be named.
+//! Number takes its value with the optional `unit` named.
+//! Slider takes its value with the optional `min`, `max`, `step`, `log` and `unit` options named.
+//! Table takes the CSV text with the optional `delimiter` and `header` options named,
+//! and Keyframes takes lists of times and values with the optional `smooth` and `loop` options.
+//! Script takes its text and then the inputs declared in its header by name.
+//! Nodes of registered kinds are written under their tag, with the inputs in order
+//! and the optional `state` as JSON text.
+//! Semicolons are optional and `//` starts a comment.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use egui_snarl::{ui::SnarlViewer as _, InPinId, NodeId, OutPinId, Snarl};
+use syn::parse::Parser as _;
+
+use super::{
+    complex::{ComplexNode, ComplexOp},
+    export::topological_order,
+    image::{Image, ImageNode, ImageOp, ShowImageNode},
+    interval::IntervalNode,
+    json::{JsonNode, JsonOp},
+    kind::{self, CustomNode, NodeKind},
+    list::{ListNode, ListOp},
+    logic::{LogicNode, LogicOp},
+    number::NumberNode,
+    pin_in_compat, pin_out_compat,
+    random::{RandomNode, RandomOp},
+    regex::RegexNode,
+    script::ScriptNode,
+    slider::SliderNode,
+    string::{self, StringNode, StringOp},
+    table::{Header, TableNode},
+    time::{KeyframesNode, TimeNode, TimeOp},
+    vector::{self, VectorNode, VectorOp},
+    Expr, ExprNode, NodeViewer, Nodes, PinCompat, PIN_BOOL, PIN_COLOR, PIN_LIST, PIN_NUM,
+    PIN_PIXELS, PIN_STR, PIN_VEC2, PIN_VEC3, PIN_VEC4,
+};
+
+/// Space between columns and rows of imported nodes.
+const LAYOUT_SPACING: egui::Vec2 = egui::Vec2::new(250.0, 150.0);
+
+/// Error in the text with its 1-based position.
+#[derive(Debug)]
+pub struct Error {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Pos {
+    line: usize,
+    column: usize,
+}
+
+impl Pos {
+    fn error(self, message: impl Into<String>) -> Error {
+        Error {
+            line: self.line,
+            column: self.column,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(f64),
+    Str(String),
+    Color([f64; 4]),
+    Punct(char),
+}
+
+struct Lexer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    pos: Pos,
+}
+
+impl Lexer<'_> {
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.pos.line += 1;
+            self.pos.column = 1;
+        } else {
+            self.pos.column += 1;
+        }
+        Some(c)
+    }
+
+    fn bump_while(&mut self, mut f: impl FnMut(char) -> bool) -> String {
+        let mut out = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if !f(c) {
+                break;
+            }
+            out.push(c);
+            self.bump();
+        }
+        out
+    }
+
+    fn string(&mut self, start: Pos) -> Result<String, Error> {
+        let mut out = String::new();
+        loop {
+            let pos = self.pos;
+            match self.bump() {
+                None => return Err(start.error("Unterminated string")),
+                Some('"') => return Ok(out),
+                Some('\\') => match self.bump() {
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some('0') => out.push('\0'),
+                    Some('u') if self.bump() == Some('{') => {
+                        let hex = self.bump_while(|c| c != '}');
+                        self.bump();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| pos.error("Invalid unicode escape"))?;
+                        out.push(c);
+                    }
+                    _ => return Err(pos.error("Invalid escape")),
+                },
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, Pos)>, Error> {
+        let mut tokens = Vec::new();
+
+        while let Some(&c) = self.chars.peek() {
+            let start = self.pos;
+            let token = match c {
+                c if c.is_whitespace() => {
+                    self.bump();
+                    continue;
+                }
+                '/' => {
+                    self.bump();
+                    if self.bump() != Some('/') {
+                        return Err(start.error("Unexpected `/`"));
+                    }
+                    self.bump_while(|c| c != '\n');
+                    continue;
+                }
+                '=' | '(' | ')' | '[' | ']' | ',' | ';' => {
+                    self.bump();
+                    Token::Punct(c)
+                }
+                '"' => {
+                    self.bump();
+                    Token::Str(self.string(start)?)
+                }
+                '#' => {
+                    self.bump();
+                    let hex = self.bump_while(|c| c.is_ascii_alphanumeric());
+                    let color = vector::parse_color_hex(&format!("#{hex}"))
+                        .ok_or_else(|| start.error(format!("Invalid color `#{hex}`")))?;
+                    Token::Color(color)
+                }
+                c if c.is_ascii_digit() || c == '-' || c == '.' => {
+                    let mut prev = '\0';
+                    let text = self.bump_while(|c| {
+                        let part = c.is_ascii_alphanumeric()
+                            || c == '.'
+                            || (matches!(c, '-' | '+') && matches!(prev, '\0' | 'e' | 'E'));
+                        prev = c;
+                        part
+                    });
+                    let value = text
+                        .parse()
+                        .map_err(|_| start.error(format!("Invalid number `{text}`")))?;
+                    Token::Number(value)
+                }
+                c if c.is_alphabetic() || c == '_' => {
+                    let ident = self.bump_while(|c| c.is_alphanumeric() || c == '_' || c == '.');
+                    match &*ident {
+                        "inf" => Token::Number(f64::INFINITY),
+                        "nan" => Token::Number(f64::NAN),
+                        _ => Token::Ident(ident),
+                    }
+                }
+                c => return Err(start.error(format!("Unexpected `{c}`"))),
+            };
+            tokens.push((token, start));
+        }
+
+        Ok(tokens)
+    }
+}
+
+enum Value {
+    Number(f64),
+    Str(String),
+    Bool(bool),
+    Tuple(Vec<f64>),
+    Color([f64; 4]),
+    List(Vec<Value>),
+    /// Empty image.
+    None,
+    Ref(String),
+}
+
+struct Arg {
+    name: Option<String>,
+    value: Value,
+    pos: Pos,
+}
+
+struct Statement {
+    name: Option<(String, Pos)>,
+    kind: String,
+    kind_pos: Pos,
+    args: Vec<Arg>,
+    /// Position of the closing parenthesis
+    end: Pos,
+}
+
+struct Parser {
+    tokens: Vec<(Token, Pos)>,
+    idx: usize,
+    /// Position after the last character
+    end: Pos,
+}
+
+impl Parser {
+    fn peek(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.idx + offset).map(|(token, _)| token)
+    }
+
+    fn pos(&self) -> Pos {
+        self.tokens.get(self.idx).map_or(self.end, |&(_, pos)| pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.idx)?.0.clone();
+        self.idx += 1;
+        Some(token)
+    }
+
+    fn eat(&mut self, punct: char) -> bool {
+        let eaten = self.peek(0) == Some(&Token::Punct(punct));
+        if eaten {
+            self.idx += 1;
+        }
+        eaten
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), Error> {
+        if self.eat(punct) {
+            Ok(())
+        } else {
+            Err(self.pos().error(format!("Expected `{punct}`")))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, Error> {
+        let pos = self.pos();
+        match self.next() {
+            Some(Token::Ident(ident)) => Ok(ident),
+            _ => Err(pos.error("Expected a name")),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        let pos = self.pos();
+        match self.next() {
+            Some(Token::Number(value)) => Ok(Value::Number(value)),
+            Some(Token::Str(value)) => Ok(Value::Str(value)),
+            Some(Token::Color(color)) => Ok(Value::Color(color)),
+            Some(Token::Punct('(')) => {
+                let mut components = Vec::new();
+                while !self.eat(')') {
+                    let pos = self.pos();
+                    match self.next() {
+                        Some(Token::Number(value)) => components.push(value),
+                        _ => return Err(pos.error("Expected a number")),
+                    }
+                    if !self.eat(',') && self.peek(0) != Some(&Token::Punct(')')) {
+                        return Err(self.pos().error("Expected `,` or `)`"));
+                    }
+                }
+                if !(2..=4).contains(&components.len()) {
+                    return Err(pos.error("Vectors have 2, 3 or 4 components"));
+                }
+                Ok(Value::Tuple(components))
+            }
+            Some(Token::Punct('[')) => {
+                let mut items = Vec::new();
+                while !self.eat(']') {
+                    items.push(self.value()?);
+                    if !self.eat(',') && self.peek(0) != Some(&Token::Punct(']')) {
+                        return Err(self.pos().error("Expected `,` or `]`"));
+                    }
+                }
+                Ok(Value::List(items))
+            }
+            Some(Token::Ident(name)) if name == "true" => Ok(Value::Bool(true)),
+            Some(Token::Ident(name)) if name == "false" => Ok(Value::Bool(false)),
+            Some(Token::Ident(name)) if name == "none" => Ok(Value::None),
+            Some(Token::Ident(name)) if name.ends_with('.') => match self.next() {
+                Some(Token::Str(label)) => Ok(Value::Ref(name + &*label)),
+                _ => Err(pos.error("Expected output name after `.`")),
+            },
+            Some(Token::Ident(name)) => Ok(Value::Ref(name)),
+            _ => Err(pos.error("Expected a number, string or node name")),
+        }
+    }
+
+    fn statement(&mut self) -> Result<Statement, Error> {
+        let name = if matches!(self.peek(1), Some(Token::Punct('='))) {
+            let pos = self.pos();
+            let name = self.ident()?;
+            self.expect('=')?;
+            Some((name, pos))
+        } else {
+            None
+        };
+
+        let kind_pos = self.pos();
+        let kind = self.ident()?;
+        self.expect('(')?;
+
+        let mut args = Vec::new();
+        loop {
+            let end = self.pos();
+            if self.eat(')') {
+                self.eat(';');
+                return Ok(Statement {
+                    name,
+                    kind,
+                    kind_pos,
+                    args,
+                    end,
+                });
+            }
+
+            let pos = self.pos();
+            let name = if matches!(self.peek(1), Some(Token::Punct('='))) {
+                let name = self.ident()?;
+                self.expect('=')?;
+                Some(name)
+            } else {
+                None
+            };
+            let value = self.value()?;
+            args.push(Arg { name, value, pos });
+
+            if !self.eat(',') && self.peek(0) != Some(&Token::Punct(')')) {
+                return Err(self.pos().error("Expected `,` or `)`"));
+            }
+        }
+    }
+}
+
+/// Nodes created so far, by name.
+struct Scope {
+    names: HashMap<String, NodeId>,
+    snarl: Snarl<Nodes>,
+}
+
+impl Scope {
+    fn resolve(&self, name: &str, pos: Pos) -> Result<NodeId, Error> {
+        self.names
+            .get(name)
+            .copied()
+            .ok_or_else(|| pos.error(format!("Unknown node `{name}`")))
+    }
+
+    /// Resolves `node` to its first output, or `node.output` to the one with that label.
+    fn resolve_pin(&self, name: &str, pos: Pos) -> Result<OutPinId, Error> {
+        let Some((node_name, label)) = name.split_once('.') else {
+            let node = self.resolve(name, pos)?;
+            return Ok(OutPinId { node, output: 0 });
+        };
+        let node = self.resolve(node_name, pos)?;
+
+        let outputs = NodeViewer::default().outputs(&self.snarl[node]);
+        let output = (0..outputs)
+            .position(|output| self.snarl[node].label_out(output) == label)
+            .ok_or_else(|| pos.error(format!("`{node_name}` has no output `{label}`")))?;
+        Ok(OutPinId { node, output })
+    }
+
+    /// Resolves output pin with string type.
+    fn string(&self, name: &str, pos: Pos) -> Result<OutPinId, Error> {
+        let pin = self.resolve_pin(name, pos)?;
+        if pin_out_compat(&self.snarl[pin.node], pin.output) & PIN_STR == 0 {
+            return Err(pos.error(format!("`{name}` does not output a string")));
+        }
+        Ok(pin)
+    }
+
+    /// Resolves output pin with number type.
+    fn number(&self, name: &str, pos: Pos) -> Result<OutPinId, Error> {
+        let pin = self.resolve_pin(name, pos)?;
+        if pin_out_compat(&self.snarl[pin.node], pin.output) & PIN_NUM == 0 {
+            return Err(pos.error(format!("`{name}` does not output a number")));
+        }
+        Ok(pin)
+    }
+
+    /// Resolves argument for an input accepting `compat`, either a literal or a node to wire from.
+    fn input(
+        &self,
+        arg: &Arg,
+        compat: PinCompat,
+        input: usize,
+        wires: &mut Vec<(OutPinId, usize)>,
+    ) -> Result<super::Value, Error> {
+        match arg.value {
+            Value::Number(value) if compat & PIN_NUM != 0 => Ok(super::Value::Number(value)),
+            Value::Str(ref value) if compat & PIN_STR != 0 => {
+                Ok(super::Value::String(value.clone()))
+            }
+            Value::Bool(value) if compat & PIN_BOOL != 0 => Ok(super::Value::Bool(value)),
+            Value::Tuple(ref components) if compat & tuple_compat(components) != 0 => {
+                Ok(super::Value::Vector(components.clone()))
+            }
+            Value::Color(color) if compat & PIN_COLOR != 0 => Ok(super::Value::Color(color)),
+            Value::List(_) if compat & PIN_LIST != 0 => literal(&arg.value).ok_or_else(|| {
+                arg.pos
+                    .error("Expected list of literals with the same type")
+            }),
+            Value::None if compat & PIN_PIXELS != 0 => Ok(super::Value::Image(Image::default())),
+            Value::Ref(ref name) => {
+                let remote = self.resolve_pin(name, arg.pos)?;
+                if pin_out_compat(&self.snarl[remote.node], remote.output) & compat == 0 {
+                    return Err(arg.pos.error(format!("`{name}` output has the wrong type")));
+                }
+                wires.push((remote, input));
+                Ok(self.snarl[remote.node].value_out(remote.output))
+            }
+            _ => {
+                let expected = [
+                    (PIN_NUM, "number"),
+                    (PIN_STR, "string"),
+                    (PIN_BOOL, "bool"),
+                    (PIN_VEC2, "vec2"),
+                    (PIN_VEC3, "vec3"),
+                    (PIN_VEC4, "vec4"),
+                    (PIN_COLOR, "color"),
+                    (PIN_LIST, "list"),
+                    (PIN_PIXELS, "none"),
+                ]
+                .into_iter()
+                .filter(|&(pin, _)| compat & pin != 0)
+                .map(|(_, name)| name)
+                .collect::<Vec<_>>();
+                Err(arg
+                    .pos
+                    .error(format!("Expected a {} or node name", expected.join(", "))))
+            }
+        }
+    }
+
+    /// Builds Regex node from the pattern and text arguments.
+    fn regex(&self, statement: &Statement) -> Result<(Nodes, Vec<(OutPinId, usize)>), Error> {
+        let mut wires = Vec::new();
+        let mut inputs = Vec::new();
+        for (idx, arg) in statement.args.iter().enumerate() {
+            if idx >= 2 {
+                return Err(arg.pos.error("Unexpected argument"));
+            }
+            // Wired outputs such as JSON documents are taken as their text.
+            inputs.push(self.input(arg, PIN_STR, idx, &mut wires)?.string());
+        }
+
+        let mut inputs = inputs.into_iter();
+        let mut regex_node = RegexNode::with_pattern(inputs.next().unwrap_or_default());
+        regex_node.text = inputs.next().unwrap_or_default();
+        Ok((Nodes::Regex(regex_node), wires))
+    }
+
+    /// Fills inputs of a logic or vector node, its arguments given in order of the inputs.
+    fn value_op(
+        &self,
+        mut node: Nodes,
+        statement: &Statement,
+    ) -> Result<(Nodes, Vec<(OutPinId, usize)>), Error> {
+        let mut wires = Vec::new();
+        for (idx, arg) in statement.args.iter().enumerate() {
+            if idx >= node.value_inputs().len() {
+                return Err(arg.pos.error("Unexpected argument"));
+            }
+            let compat = pin_in_compat(&node, idx);
+            node.value_inputs()[idx] = self.input(arg, compat, idx, &mut wires)?;
+        }
+
+        Ok((node, wires))
+    }
+
+    /// Builds Script node from its text and the values of the inputs declared in it, given by name.
+    fn script(&self, statement: &Statement) -> Result<(Nodes, Vec<(OutPinId, usize)>), Error> {
+        let mut args = statement.args.iter();
+        let text = match args.next() {
+            Some(Arg {
+                name: None,
+                value: Value::Str(text),
+                ..
+            }) => text.clone(),
+            Some(arg) => return Err(arg.pos.error("Expected script text")),
+            None => return Err(statement.end.error("Script needs script text")),
+        };
+
+        let mut wires = Vec::new();
+        let mut script_node = ScriptNode::with_text(text);
+        for arg in args {
+            let Some(ref name) = arg.name else {
+                return Err(arg.pos.error("Expected input name"));
+            };
+            let idx = script_node
+                .ins
+                .iter()
+                .position(|port| port.name == *name)
+                .ok_or_else(|| arg.pos.error(format!("Script has no input `{name}`")))?;
+            let compat = script_node.in_compat(idx);
+            script_node.inputs[idx] = self.input(arg, compat, idx, &mut wires)?;
+        }
+
+        Ok((Nodes::Script(script_node), wires))
+    }
+
+    /// Builds node of a registered kind from the values of its inputs in order and its `state`.
+    fn custom(
+        &self,
+        mut kind: Box<dyn NodeKind>,
+        statement: &Statement,
+    ) -> Result<(Nodes, Vec<(OutPinId, usize)>), Error> {
+        let mut positional = Vec::new();
+        for arg in &statement.args {
+            match (arg.name.as_deref(), &arg.value) {
+                (Some("state"), Value::Str(state)) => {
+                    let state = serde_json::from_str(state)
+                        .map_err(|err| arg.pos.error(format!("Invalid state: {err}")))?;
+                    kind.load(state).map_err(|err| arg.pos.error(err))?;
+                }
+                (Some("state"), _) => return Err(arg.pos.error("Expected state as JSON text")),
+                (Some(name), _) => {
+                    return Err(arg
+                        .pos
+                        .error(format!("{} has no option `{name}`", statement.kind)));
+                }
+                (None, _) => positional.push(arg),
+            }
+        }
+
+        // Ports may depend on the state, so inputs are set once it is loaded.
+        let mut wires = Vec::new();
+        let mut node = Nodes::Custom(CustomNode::new(kind));
+        for (idx, arg) in positional.into_iter().enumerate() {
+            if idx >= node.value_inputs().len() {
+                return Err(arg.pos.error("Unexpected argument"));
+            }
+            let compat = pin_in_compat(&node, idx);
+            node.value_inputs()[idx] = self.input(arg, compat, idx, &mut wires)?;
+        }
+
+        Ok((node, wires))
+    }
+
+    /// Builds string manipulation node, its arguments given in order of the inputs.
+    fn string_op(
+        &self,
+        op: StringOp,
+        statement: &Statement,
+    ) -> Result<(Nodes, Vec<(OutPinId, usize)>), Error> {
+        let mut wires = Vec::new();
+        let mut string_node = StringNode::new(op);
+        let mut args = statement.args.iter();
+
+        if op == StringOp::Format {
+            if let Some(arg) = args.next() {
+                if arg.name.is_some() {
+                    return Err(arg.pos.error("Expected template"));
+                }
+                string_node.inputs[0] = self.input(arg, PIN_STR, 0, &mut wires)?;
+                string_node.placeholders = string::placeholders(string_node.template());
+                string_node.inputs.resize(
+                    1 + string_node.placeholders.len(),
+                    super::Value::String(String::new()),
+                );
+            }
+        }
+
+        let mut positional = usize::from(op == StringOp::Format);
+        for arg in args {
+            let idx = match arg.name {
+                Some(ref name) => {
+                    let idx = string_node
+                        .placeholders
+                        .iter()
+                        .position(|placeholder| placeholder == name)
+                        .ok_or_else(|| {
+                            arg.pos.error(format!("Format has no placeholder `{name}`"))
+                        })?;
+                    idx + 1
+                }
+                None => {
+                    positional += 1;
+                    positional - 1
+                }
+            };
+
+            // Concat keeps one spare input after the given ones.
+            if op == StringOp::Concat {
+                string_node
+                    .inputs
+                    .resize(idx + 2, super::Value::String(String::new()));
+            }
+            if idx >= string_node.inputs.len() {
+                return Err(arg.pos.error("Unexpected argument"));
+            }
+
+            let compat = string_node.in_compat(idx);
+            string_node.inputs[idx] = self.input(arg, compat, idx, &mut wires)?;
+        }
+
+        Ok((Nodes::StringOp(string_node), wires))
+    }
+
+    /// Builds node of the statement and returns it with wires into its inputs.
+    fn node(&self, statement: &Statement) -> Result<(Nodes, Vec<(OutPinId, usize)>), Error> {
+        let mut wires = Vec::new();
+        let mut args = statement.args.iter();
+
+        if let Some(arg) = statement.args.iter().find(|arg| arg.name.is_some()) {
+            if !matches!(
+                &*statement.kind,
+                "Number" | "Expr" | "Slider" | "Table" | "Keyframes" | "Script"
+            ) && statement.kind != StringOp::Format.ident()
+                && !kind::is_registered(&statement.kind)
+            {
+                return Err(arg.pos.error(
+                    "Only Number, Expr, Format, Slider, Table, Keyframes, Script and custom nodes take named arguments",
+                ));
+            }
+        }
+
+        if let Some(op) = StringOp::from_ident(&statement.kind) {
+            return self.string_op(op, statement);
+        }
+        if let Some(op) = LogicOp::from_ident(&statement.kind) {
+            return self.value_op(Nodes::Logic(LogicNode::new(op)), statement);
+        }
+        if let Some(op) = VectorOp::from_ident(&statement.kind) {
+            return self.value_op(Nodes::Vector(VectorNode::new(op)), statement);
+        }
+        if let Some(op) = ListOp::from_ident(&statement.kind) {
+            return self.value_op(Nodes::List(ListNode::new(op)), statement);
+        }
+        if let Some(op) = JsonOp::from_ident(&statement.kind) {
+            return self.value_op(Nodes::Json(JsonNode::new(op)), statement);
+        }
+        if let Some(op) = ImageOp::from_ident(&statement.kind) {
+            return self.value_op(Nodes::Image(ImageNode::new(op)), statement);
+        }
+        if let Some(op) = TimeOp::from_ident(&statement.kind) {
+            return self.value_op(Nodes::Time(TimeNode::new(op)), statement);
+        }
+        if let Some(op) = RandomOp::from_ident(&statement.kind) {
+            return self.value_op(Nodes::Random(RandomNode::new(op)), statement);
+        }
+        if let Some(op) = ComplexOp::from_ident(&statement.kind) {
+            return self.value_op(Nodes::Complex(ComplexNode::new(op)), statement);
+        }
+        if statement.kind == "Regex" {
+            return self.regex(statement);
+        }
+        if statement.kind == "Number" {
+            return number(statement).map(|node| (node, Vec::new()));
+        }
+        if statement.kind == "Interval" {
+            return interval(statement).map(|node| (node, Vec::new()));
+        }
+        if statement.kind == "Slider" {
+            return slider(statement).map(|node| (node, Vec::new()));
+        }
+        if statement.kind == "Table" {
+            return table(statement).map(|node| (node, Vec::new()));
+        }
+        if statement.kind == "Keyframes" {
+            return keyframes(statement).map(|node| (node, Vec::new()));
+        }
+        if statement.kind == "Script" {
+            return self.script(statement);
+        }
+        if let Some(kind) = kind::create(&statement.kind) {
+            return self.custom(kind, statement);
+        }
+
+        let node = match (&*statement.kind, args.next()) {
+            ("String", None) => Nodes::String(String::new()),
+            (
+                "String",
+                Some(Arg {
+                    value: Value::Str(value),
+                    ..
+                }),
+            ) => Nodes::String(value.clone()),
+            ("Toggle", None) => Nodes::Toggle(false),
+            (
+                "Toggle",
+                Some(&Arg {
+                    value: Value::Bool(value),
+                    ..
+                }),
+            ) => Nodes::Toggle(value),
+            ("Color", None) => Nodes::Color([1.0; 4]),
+            (
+                "Color",
+                Some(&Arg {
+                    value: Value::Color(color),
+                    ..
+                }),
+            ) => Nodes::Color(color),
+            ("ShowImage", None) => Nodes::ShowImage(ShowImageNode::default()),
+            (
+                "ShowImage",
+                Some(Arg {
+                    value: Value::Str(uri),
+                    ..
+                }),
+            ) => Nodes::ShowImage(ShowImageNode::new(uri.clone())),
+            (
+                "ShowImage",
+                Some(Arg {
+                    value: Value::Ref(name),
+                    pos,
+                    ..
+                }),
+            ) => {
+                let remote = self.string(name, *pos)?;
+                wires.push((remote, 0));
+                Nodes::ShowImage(ShowImageNode::new(
+                    self.snarl[remote.node].string_out(remote.output),
+                ))
+            }
+            ("Sink", None) => Nodes::Sink,
+            (
+                "Sink",
+                Some(Arg {
+                    value: Value::Ref(name),
+                    pos,
+                    ..
+                }),
+            ) => {
+                let remote = self.resolve_pin(name, *pos)?;
+                if let Nodes::Sink = self.snarl[remote.node] {
+                    return Err(pos.error(format!("`{name}` has no output")));
+                }
+                wires.push((remote, 0));
+                Nodes::Sink
+            }
+            ("Expr", Some(text_arg)) if text_arg.name.is_none() => {
+                let text = match text_arg.value {
+                    Value::Str(ref text) => text.clone(),
+                    Value::Ref(ref name) => {
+                        let remote = self.string(name, text_arg.pos)?;
+                        wires.push((remote, 0));
+                        self.snarl[remote.node].string_out(remote.output)
+                    }
+                    Value::Number(_)
+                    | Value::Bool(_)
+                    | Value::Tuple(_)
+                    | Value::Color(_)
+                    | Value::List(_)
+                    | Value::None => {
+                        return Err(text_arg.pos.error("Expected expression text"));
+                    }
+                };
+
+                let mut expr_node = ExprNode::with_text(text)
+                    .map_err(|err| text_arg.pos.error(format!("Invalid expression: {err}")))?;
+
+                for arg in args.by_ref() {
+                    let Some(ref name) = arg.name else {
+                        return Err(arg.pos.error("Expected binding name"));
+                    };
+                    let idx = expr_node
+                        .bindings
+                        .iter()
+                        .position(|binding| binding == name)
+                        .ok_or_else(|| arg.pos.error(format!("Expr has no binding `{name}`")))?;
+
+                    expr_node.values[idx] = match arg.value {
+                        Value::Number(value) => value,
+                        Value::Ref(ref remote) => {
+                            let remote = self.number(remote, arg.pos)?;
+                            wires.push((remote, idx + 1));
+                            self.snarl[remote.node].number_out(remote.output)
+                        }
+                        Value::Str(_)
+                        | Value::Bool(_)
+                        | Value::Tuple(_)
+                        | Value::Color(_)
+                        | Value::List(_)
+                        | Value::None => {
+                            return Err(arg.pos.error("Expected a number or node name"));
+                        }
+                    };
+                }
+
+                Nodes::ExprNode(expr_node)
+            }
+            ("String" | "Toggle" | "Color" | "ShowImage" | "Sink" | "Expr", Some(arg)) => {
+                return Err(arg
+                    .pos
+                    .error(format!("Invalid argument for {}", statement.kind)));
+            }
+            ("Expr", None) => {
+                return Err(statement.end.error("Expr needs expression text"));
+            }
+            (kind, _) => {
+                return Err(statement
+                    .kind_pos
+                    .error(format!("Unknown node kind `{kind}`")));
+            }
+        };
+
+        if let Some(arg) = args.next() {
+            return Err(arg.pos.error("Unexpected argument"));
+        }
+
+        Ok((node, wires))
+    }
+}
+
+/// Builds Table node from the CSV text and the named `delimiter` and `header` options.
+fn table(statement: &Statement) -> Result<Nodes, Error> {
+    let mut text = None;
+    let mut delimiter = b',';
+    let mut header = Header::Auto;
+    for arg in &statement.args {
+        match (arg.name.as_deref(), &arg.value) {
+            (None, Value::Str(value)) if text.is_none() => text = Some(value.clone()),
+            (Some("delimiter"), Value::Str(value)) => match *value.as_bytes() {
+                [byte] => delimiter = byte,
+                _ => return Err(arg.pos.error("Delimiter must be a single ASCII character")),
+            },
+            (Some("header"), &Value::Bool(value)) => {
+                header = if value { Header::Yes } else { Header::No };
+            }
+            (Some(name), _) if !matches!(name, "delimiter" | "header") => {
+                return Err(arg.pos.error(format!("Table has no option `{name}`")));
+            }
+            _ => return Err(arg.pos.error("Invalid argument for Table")),
+        }
+    }
+
+    let text = text.ok_or_else(|| statement.end.error("Table needs CSV text"))?;
+    Ok(Nodes::Table(TableNode::with_text(text, delimiter, header)))
+}
+
+fn number(statement: &Statement) -> Result<Nodes, Error> {
+    let mut number_node = NumberNode::new(0.0);
+    let mut value = None;
+    for arg in &statement.args {
+        match (arg.name.as_deref(), &arg.value) {
+            (None, &Value::Number(number)) if value.is_none() => value = Some(number),
+            (Some("unit"), Value::Str(unit)) => number_node.unit.clone_from(unit),
+            (Some(name), _) if name != "unit" => {
+                return Err(arg.pos.error(format!("Number has no option `{name}`")));
+            }
+            _ => return Err(arg.pos.error("Invalid argument for Number")),
+        }
+    }
+
+    number_node.value = value.unwrap_or_default();
+    Ok(Nodes::Number(number_node))
+}
+
+fn interval(statement: &Statement) -> Result<Nodes, Error> {
+    match *statement.args {
+        [Arg {
+            value: Value::Number(value),
+            ..
+        }, Arg {
+            value: Value::Number(tolerance),
+            ..
+        }] => Ok(Nodes::Interval(IntervalNode { value, tolerance })),
+        _ => Err(statement
+            .kind_pos
+            .error("Interval needs a value and a tolerance")),
+    }
+}
+
+fn slider(statement: &Statement) -> Result<Nodes, Error> {
+    let mut slider_node = SliderNode::new();
+    let mut value = None;
+    for arg in &statement.args {
+        match (arg.name.as_deref(), &arg.value) {
+            (None, &Value::Number(number)) if value.is_none() => value = Some(number),
+            (Some("min"), &Value::Number(min)) => slider_node.min = min,
+            (Some("max"), &Value::Number(max)) => slider_node.max = max,
+            (Some("step"), &Value::Number(step)) if step >= 0.0 => slider_node.step = step,
+            (Some("log"), &Value::Bool(logarithmic)) => slider_node.logarithmic = logarithmic,
+            (Some("unit"), Value::Str(unit)) => slider_node.unit.clone_from(unit),
+            (Some(name), _) if !matches!(name, "min" | "max" | "step" | "log" | "unit") => {
+                return Err(arg.pos.error(format!("Slider has no option `{name}`")));
+            }
+            _ => return Err(arg.pos.error("Invalid argument for Slider")),
+        }
+    }
+
+    slider_node.value = slider_node.constrain(value.unwrap_or(slider_node.min));
+    Ok(Nodes::Slider(slider_node))
+}
+
+fn keyframes(statement: &Statement) -> Result<Nodes, Error> {
+    let mut lists = Vec::new();
+    let mut smooth = false;
+    let mut looped = false;
+    for arg in &statement.args {
+        match (arg.name.as_deref(), &arg.value) {
+            (None, Value::List(items)) if lists.len() < 2 => {
+                let numbers = items
+                    .iter()
+                    .map(|item| match *item {
+                        Value::Number(value) => Some(value),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| arg.pos.error("Expected list of numbers"))?;
+                lists.push((numbers, arg.pos));
+            }
+            (Some("smooth"), &Value::Bool(value)) => smooth = value,
+            (Some("loop"), &Value::Bool(value)) => looped = value,
+            (Some(name), _) if !matches!(name, "smooth" | "loop") => {
+                return Err(arg.pos.error(format!("Keyframes has no option `{name}`")));
+            }
+            _ => return Err(arg.pos.error("Invalid argument for Keyframes")),
+        }
+    }
+
+    let [(times, _), (values, pos)] = <[_; 2]>::try_from(lists).map_err(|_| {
+        statement
+            .end
+            .error("Keyframes needs lists of times and values")
+    })?;
+    if times.len() != values.len() {
+        return Err(pos.error("Keyframes needs as many values as times"));
+    }
+    let keys = times
+        .into_iter()
+        .zip(values)
+        .map(|(time, value)| [time, value])
+        .collect();
+    Ok(Nodes::Keyframes(KeyframesNode::with_keys(
+        keys, smooth, looped,
+    )))
+}
+
+/// Value of a literal, lists only if all their elements have the same type.
+fn literal(value: &Value) -> Option<super::Value> {
+    match *value {
+        Value::Number(value) => Some(super::Value::Number(value)),
+        Value::Str(ref value) => Some(super::Value::String(value.clone())),
+        Value::Bool(value) => Some(super::Value::Bool(value)),
+        Value::Tuple(ref components) => Some(super::Value::Vector(components.clone())),
+        Value::Color(color) => Some(super::Value::Color(color)),
+        Value::List(ref items) => {
+            let items = items.iter().map(literal).collect::<Option<Vec<_>>>()?;
+            let same = items
+                .windows(2)
+                .all(|pair| pair[0].compat() == pair[1].compat());
+            same.then_some(super::Value::List(items))
+        }
+        Value::None | Value::Ref(_) => None,
+    }
+}
+
+/// Vector type of a tuple literal.
+fn tuple_compat(components: &[f64]) -> PinCompat {
+    match components.len() {
+        2 => PIN_VEC2,
+        3 => PIN_VEC3,
+        4 => PIN_VEC4,
+        _ => 0,
+    }
+}
+
+/// Builds a graph from text, laying nodes out in columns by their distance from the sources.
+pub fn import(text: &str) -> Result<Snarl<Nodes>, Error> {
+    let mut end = Pos { line: 1, column: 1 };
+    let lexer = Lexer {
+        chars: text.chars().peekable(),
+        pos: end,
+    };
+    let tokens = lexer.tokenize()?;
+    for c in text.chars() {
+        if c == '\n' {
+            end.line += 1;
+            end.column = 1;
+        } else {
+            end.column += 1;
+        }
+    }
+
+    let mut parser = Parser {
+        tokens,
+        idx: 0,
+        end,
+    };
+    let mut scope = Scope {
+        names: HashMap::new(),
+        snarl: Snarl::new(),
+    };
+
+    // Column of each node, and number of nodes in each column.
+    let mut columns = HashMap::<NodeId, usize>::new();
+    let mut rows = Vec::<usize>::new();
+
+    while parser.peek(0).is_some() {
+        let statement = parser.statement()?;
+        if let Some((ref name, pos)) = statement.name {
+            if name.contains('.') {
+                return Err(pos.error("Node names can't contain `.`"));
+            }
+            if scope.names.contains_key(name) {
+                return Err(pos.error(format!("Node `{name}` is already defined")));
+            }
+        }
+        let (node, wires) = scope.node(&statement)?;
+
+        let column = wires
+            .iter()
+            .map(|(remote, _)| columns[&remote.node] + 1)
+            .max()
+            .unwrap_or(0);
+        if rows.len() <= column {
+            rows.resize(column + 1, 0);
+        }
+        let pos = egui::pos2(
+            column as f32 * LAYOUT_SPACING.x,
+            rows[column] as f32 * LAYOUT_SPACING.y,
+        );
+        rows[column] += 1;
+
+        let id = scope.snarl.insert_node(pos, node);
+        columns.insert(id, column);
+        for (remote, input) in wires {
+            scope.snarl.connect(remote, InPinId { node: id, input });
+        }
+
+        if let Some((name, _)) = statement.name {
+            scope.names.insert(name, id);
+        }
+    }
+
+    Ok(scope.snarl)
+}
+
+/// Writes the graph as text that [`import`] turns back into the same graph.
+pub fn export(snarl: &Snarl<Nodes>) -> Result<String, String> {
+    let order = topological_order(snarl).ok_or("Graph has a cycle")?;
+
+    let name = |node: NodeId| {
+        let kind = match snarl[node] {
+            Nodes::Sink => "sink",
+            Nodes::Number(_) => "number",
+            Nodes::Slider(_) => "slider",
+            Nodes::Interval(_) => "interval",
+            Nodes::String(_) => "string",
+            Nodes::ShowImage(_) => "image",
+            Nodes::ExprNode(_) => "expr",
+            Nodes::StringOp(ref string_node) => {
+                return format!("{}_{}", string_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::Regex(_) => "regex",
+            Nodes::Toggle(_) => "toggle",
+            Nodes::Logic(ref logic_node) => {
+                return format!("{}_{}", logic_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::Color(_) => "color",
+            Nodes::Vector(ref vector_node) => {
+                return format!("{}_{}", vector_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::List(ref list_node) => {
+                return format!("{}_{}", list_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::Table(_) => "table",
+            Nodes::Json(ref json_node) => {
+                return format!("{}_{}", json_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::Image(ref image_node) => {
+                return format!("{}_{}", image_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::Time(ref time_node) => {
+                return format!("{}_{}", time_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::Keyframes(_) => "keyframes",
+            Nodes::Random(ref random_node) => {
+                return format!("{}_{}", random_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::Complex(ref complex_node) => {
+                return format!("{}_{}", complex_node.op.ident().to_lowercase(), node.0);
+            }
+            Nodes::Script(_) => "script",
+            Nodes::Custom(ref custom_node) => {
+                return format!("{}_{}", custom_node.kind.tag().to_lowercase(), node.0);
+            }
+        };
+        format!("{kind}_{}", node.0)
+    };
+    let remote = |node: NodeId, input: usize| match *snarl.in_pin(InPinId { node, input }).remotes {
+        [remote] if remote.output == 0 => Some(name(remote.node)),
+        [remote] => {
+            let label = snarl[remote.node].label_out(remote.output);
+            let plain = label.chars().all(|c| c.is_alphanumeric() || c == '_');
+            Some(if plain && !label.is_empty() {
+                format!("{}.{label}", name(remote.node))
+            } else {
+                format!("{}.{label:?}", name(remote.node))
+            })
+        }
+        _ => None,
+    };
+
+    let mut code = String::new();
+    for node in order {
+        match snarl[node] {
+            Nodes::Sink => {
+                let _ = writeln!(code, "Sink({});", remote(node, 0).unwrap_or_default());
+            }
+            Nodes::Number(ref number_node) => {
+                let _ = write!(code, "{} = Number({}", name(node), Num(number_node.value));
+                if !number_node.unit.is_empty() {
+                    let _ = write!(code, ", unit = {:?}", number_node.unit);
+                }
+                code.push_str(");\n");
+            }
+            Nodes::Interval(ref interval_node) => {
+                let _ = writeln!(
+                    code,
+                    "{} = Interval({}, {});",
+                    name(node),
+                    Num(interval_node.value),
+                    Num(interval_node.tolerance)
+                );
+            }
+            Nodes::Slider(ref slider_node) => {
+                let _ = write!(
+                    code,
+                    "{} = Slider({}, min = {}, max = {}",
+                    name(node),
+                    Num(slider_node.value_out()),
+                    Num(slider_node.min),
+                    Num(slider_node.max)
+                );
+                if slider_node.step > 0.0 {
+                    let _ = write!(code, ", step = {}", Num(slider_node.step));
+                }
+                if slider_node.logarithmic {
+                    code.push_str(", log = true");
+                }
+                if !slider_node.unit.is_empty() {
+                    let _ = write!(code, ", unit = {:?}", slider_node.unit);
+                }
+                code.push_str(");\n");
+            }
+            Nodes::String(ref value) => {
+                let _ = writeln!(code, "{} = String({value:?});", name(node));
+            }
+            Nodes::ShowImage(ref show_image) => {
+                let uri = remote(node, 0).unwrap_or_else(|| format!("{:?}", show_image.uri));
+                let _ = writeln!(code, "{} = ShowImage({uri});", name(node));
+            }
+            Nodes::ExprNode(ref expr_node) => {
+                let _ = write!(code, "{} = Expr(", name(node));
+                match remote(node, 0) {
+                    Some(remote) => code.push_str(&remote),
+                    None => {
+                        // Text being edited may not parse, the expression it last parsed to does.
+                        let text = match Expr::parse_block.parse_str(&expr_node.text) {
+                            Ok(_) => expr_node.text.clone(),
+                            Err(_) => expr_node.expr.to_string(),
+                        };
+                        let _ = write!(code, "{text:?}");
+                    }
+                }
+                for (idx, binding) in expr_node.bindings.iter().enumerate() {
+                    match remote(node, idx + 1) {
+                        Some(remote) => {
+                            let _ = write!(code, ", {binding} = {remote}");
+                        }
+                        None => {
+                            let _ = write!(code, ", {binding} = {}", Num(expr_node.values[idx]));
+                        }
+                    }
+                }
+                code.push_str(");\n");
+            }
+            Nodes::StringOp(ref string_node) => {
+                let _ = write!(code, "{} = {}(", name(node), string_node.op.ident());
+                let mut inputs = string_node.inputs.len();
+                let spare = matches!(string_node.inputs.last(), Some(super::Value::String(value)) if value.is_empty());
+                if string_node.op == StringOp::Concat && spare && remote(node, inputs - 1).is_none()
+                {
+                    // The spare input is added back on import.
+                    inputs -= 1;
+                }
+                write_values(&mut code, &string_node.inputs[..inputs], |idx| {
+                    remote(node, idx)
+                });
+                code.push_str(");\n");
+            }
+            Nodes::Toggle(value) => {
+                let _ = writeln!(code, "{} = Toggle({value});", name(node));
+            }
+            Nodes::Logic(ref logic_node) => {
+                let _ = write!(code, "{} = {}(", name(node), logic_node.op.ident());
+                write_values(&mut code, &logic_node.inputs, |idx| remote(node, idx));
+                code.push_str(");\n");
+            }
+            Nodes::Color(color) => {
+                let _ = writeln!(
+                    code,
+                    "{} = Color({});",
+                    name(node),
+                    vector::color_hex(color)
+                );
+            }
+            Nodes::Vector(ref vector_node) => {
+                let _ = write!(code, "{} = {}(", name(node), vector_node.op.ident());
+                write_values(&mut code, &vector_node.inputs, |idx| remote(node, idx));
+                code.push_str(");\n");
+            }
+            Nodes::Table(ref table_node) => {
+                let _ = write!(code, "{} = Table({:?}", name(node), table_node.text);
+                if table_node.delimiter != b',' {
+                    let _ = write!(
+                        code,
+                        ", delimiter = {:?}",
+                        char::from(table_node.delimiter).to_string()
+                    );
+                }
+                match table_node.header {
+                    Header::Auto => {}
+                    Header::Yes => code.push_str(", header = true"),
+                    Header::No => code.push_str(", header = false"),
+                }
+                code.push_str(");\n");
+            }
+            Nodes::List(ref list_node) => {
+                let _ = write!(code, "{} = {}(", name(node), list_node.op.ident());
+                write_values(&mut code, &list_node.inputs, |idx| remote(node, idx));
+                code.push_str(");\n");
+            }
+            Nodes::Json(ref json_node) => {
+                let _ = write!(code, "{} = {}(", name(node), json_node.op.ident());
+                write_values(&mut code, &json_node.inputs, |idx| remote(node, idx));
+                code.push_str(");\n");
+            }
+            Nodes::Image(ref image_node) => {
+                let _ = write!(code, "{} = {}(", name(node), image_node.op.ident());
+                write_values(&mut code, &image_node.inputs, |idx| remote(node, idx));
+                code.push_str(");\n");
+            }
+            Nodes::Time(ref time_node) => {
+                let _ = write!(code, "{} = {}(", name(node), time_node.op.ident());
+                write_values(&mut code, &time_node.inputs, |idx| remote(node, idx));
+                code.push_str(");\n");
+            }
+            Nodes::Random(ref random_node) => {
+                let _ = write!(code, "{} = {}(", name(node), random_node.op.ident());
+                write_values(&mut code, &random_node.inputs, |idx| remote(node, idx));
+                code.push_str(");\n");
+            }
+            Nodes::Complex(ref complex_node) => {
+                let _ = write!(code, "{} = {}(", name(node), complex_node.op.ident());
+                write_values(&mut code, &complex_node.inputs, |idx| remote(node, idx));
+                code.push_str(");\n");
+            }
+            Nodes::Script(ref script_node) => {
+                let _ = write!(code, "{} = Script({:?}", name(node), script_node.text);
+                for (idx, port) in script_node.ins.iter().enumerate() {
+                    let _ = write!(code, ", {} = ", port.name);
+                    match remote(node, idx) {
+                        Some(remote) => code.push_str(&remote),
+                        None => write_value(&mut code, &script_node.inputs[idx]),
+                    }
+                }
+                code.push_str(");\n");
+            }
+            Nodes::Custom(ref custom_node) => {
+                let _ = write!(code, "{} = {}(", name(node), custom_node.kind.tag());
+                write_values(&mut code, &custom_node.inputs, |idx| remote(node, idx));
+                let state = custom_node.kind.save();
+                if !state.is_null() {
+                    if !custom_node.inputs.is_empty() {
+                        code.push_str(", ");
+                    }
+                    let _ = write!(code, "state = {:?}", state.to_string());
+                }
+                code.push_str(");\n");
+            }
+            Nodes::Keyframes(ref keyframes_node) => {
+                let column = |idx: usize| {
+                    let items = keyframes_node
+                        .keys
+                        .iter()
+                        .map(|key| Num(key[idx]).to_string())
+                        .collect::<Vec<_>>();
+                    format!("[{}]", items.join(", "))
+                };
+                let _ = write!(
+                    code,
+                    "{} = Keyframes({}, {}",
+                    name(node),
+                    column(0),
+                    column(1)
+                );
+                if keyframes_node.smooth {
+                    code.push_str(", smooth = true");
+                }
+                if keyframes_node.looped {
+                    code.push_str(", loop = true");
+                }
+                code.push_str(");\n");
+            }
+            Nodes::Regex(ref regex_node) => {
+                let pattern =
+                    remote(node, 0).unwrap_or_else(|| format!("{:?}", regex_node.pattern));
+                let text = remote(node, 1).unwrap_or_else(|| format!("{:?}", regex_node.text));
+                let _ = writeln!(code, "{} = Regex({pattern}, {text});", name(node));
+            }
+        }
+    }
+
+    Ok(code)
+}
+
+/// Number written so that the lexer reads it back, including infinities and NaN.
+struct Num(f64);
+
+impl std::fmt::Display for Num {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            value if value.is_nan() => f.write_str("nan"),
+            f64::INFINITY => f.write_str("inf"),
+            f64::NEG_INFINITY => f.write_str("-inf"),
+            value => write!(f, "{value:?}"),
+        }
+    }
+}
+
+/// Writes arguments for inputs that cache their values, the wired node or else the value.
+fn write_values(
+    code: &mut String,
+    values: &[super::Value],
+    remote: impl Fn(usize) -> Option<String>,
+) {
+    for (idx, value) in values.iter().enumerate() {
+        if idx > 0 {
+            code.push_str(", ");
+        }
+        match remote(idx) {
+            Some(remote) => code.push_str(&remote),
+            None => write_value(code, value),
+        }
+    }
+}
+
+/// Writes value as a literal.
+fn write_value(code: &mut String, value: &super::Value) {
+    match *value {
+        super::Value::Number(value) => {
+            let _ = write!(code, "{}", Num(value));
+        }
+        super::Value::String(ref value) => {
+            let _ = write!(code, "{value:?}");
+        }
+        super::Value::Bool(value) => {
+            let _ = write!(code, "{value}");
+        }
+        super::Value::Vector(ref components) => {
+            let components = components
+                .iter()
+                .map(|&component| Num(component).to_string())
+                .collect::<Vec<_>>();
+            let _ = write!(code, "({})", components.join(", "));
+        }
+        super::Value::Color(color) => {
+            code.push_str(&vector::color_hex(color));
+        }
+        // Inputs taking documents also take JSON text.
+        super::Value::Json(ref json) => {
+            let _ = write!(code, "{:?}", json.to_string());
+        }
+        // Pixels are pulled again through the wires.
+        super::Value::Image(_) => code.push_str("none"),
+        super::Value::List(ref items) => {
+            code.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                if idx > 0 {
+                    code.push_str(", ");
+                }
+                write_value(code, item);
+            }
+            code.push(']');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Exports the graph of `text`, checking that importing the export gives the same export.
+    fn round_trip(text: &str) -> String {
+        let exported = export(&import(text).unwrap()).unwrap();
+        let again = export(&import(&exported).unwrap()).unwrap();
+        assert_eq!(exported, again);
+        exported
+    }
+
+    #[test]
+    fn round_trips_graphs() {
+        let exported = round_trip(
+            r#"
+            a = Number(3);
+            s = Slider(0.25, min = 0, max = 2, step = 0.25, unit = "m");
+            e = Expr("a * x + 1", a = a, x = s);
+            t = Toggle(true);
+            n = Not(t);
+            c = Color(#ff8000ff);
+            j = Concat("a", "b");
+            r = Regex("(?<word>\\w+)", j);
+            k = Keyframes([0, 1], [2, 3], smooth = true);
+            d = Number(1.5, unit = "km");
+            Sink(e);
+            Sink(n);
+            Sink(c);
+            Sink(r.word);
+            Sink(k);
+            Sink(d);
+            "#,
+        );
+        assert!(exported.contains(r#"Number(1.5, unit = "km")"#));
+        assert!(exported.contains(r#"Regex("(?<word>\\w+)", concat_"#));
+        assert!(exported.contains(".word);"));
+    }
+
+    #[test]
+    fn round_trips_scripts() {
+        let exported = round_trip(
+            r#"
+            a = Number(2);
+            s = Script("// in: x, label: string\n// out: y\nlet y = x * 2;\n", x = a, label = "twice");
+            Sink(s);
+            "#,
+        );
+        assert!(exported.contains(r#"label = "twice""#));
+        assert!(exported.contains("x = number_0"));
+    }
+
+    #[test]
+    fn quotes_output_names() {
+        let exported = round_trip(
+            r#"
+            t = Table("item,unit price\na,1.5\nb,2", header = true);
+            Sink(t."unit price");
+            "#,
+        );
+        assert!(exported.contains(r#"Sink(table_0."unit price");"#));
+    }
+
+    #[test]
+    fn round_trips_non_finite_numbers() {
+        let exported = round_trip(
+            "a = Number(inf);\nb = Number(-inf);\nc = Number(nan);\nv = VectorLength((1e300, nan));\n",
+        );
+        assert!(exported.contains("Number(inf)"));
+        assert!(exported.contains("Number(-inf)"));
+        assert!(exported.contains("Number(nan)"));
+        assert!(exported.contains("(1e300, nan)"));
+    }
+
+    #[test]
+    fn keeps_expr_text() {
+        let exported = round_trip(r#"e = Expr("let t = x * 2; t + 1", x = 1); Sink(e);"#);
+        assert!(exported.contains(r#"Expr("let t = x * 2; t + 1", x = 1.0)"#));
+    }
+
+    /// Finds the only Expr node of the graph.
+    fn expr_node(snarl: &Snarl<Nodes>) -> &ExprNode {
+        snarl
+            .node_ids()
+            .find_map(|(_, node)| match node {
+                Nodes::ExprNode(expr_node) => Some(expr_node),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn wires_json_queries_into_exprs() {
+        let snarl = import(
+            r#"
+            doc = JsonParse("{\"a\": 2, \"f\": \"x * 3\"}");
+            a = JsonQuery(doc, "$.a");
+            f = JsonQuery(doc, "$.f");
+            e = Expr(f, x = a);
+            Sink(e);
+            "#,
+        )
+        .unwrap();
+        let expr_node = expr_node(&snarl);
+        assert_eq!(expr_node.text, "x * 3");
+        assert_eq!(expr_node.values, [2.0]);
+    }
+
+    #[test]
+    fn wires_list_elements_into_exprs() {
+        let snarl = import(
+            r#"
+            f = Index(["x + 1", "x * 3"], 1);
+            e = Expr(f, x = 2);
+            Sink(e);
+            "#,
+        )
+        .unwrap();
+        assert_eq!(expr_node(&snarl).text, "x * 3");
+    }
+}
@@ -0,0 +1,556 @@
+//! Exporting graphs as text in other languages.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+use egui::Color32;
+use egui_snarl::{ui::SnarlViewer as _, InPinId, NodeId, OutPinId, Snarl};
+
+use super::{units, BinOp, Expr, NodeViewer, Nodes, RustExpr};
+
+/// Text formats a graph can be exported to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Rust,
+    Dot,
+    Mermaid,
+    Text,
+}
+
+impl Format {
+    pub const ALL: [Format; 4] = [Format::Rust, Format::Dot, Format::Mermaid, Format::Text];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Format::Rust => "Rust",
+            Format::Dot => "DOT",
+            Format::Mermaid => "Mermaid",
+            Format::Text => "Text",
+        }
+    }
+
+    /// Finds format by its case-insensitive name.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Writes the graph in this format, values in node labels rounded to `precision` decimals.
+    pub fn export(self, snarl: &Snarl<Nodes>, precision: u8) -> Result<String, String> {
+        match self {
+            Format::Rust => rust(snarl),
+            Format::Dot => Ok(dot(snarl, precision)),
+            Format::Mermaid => Ok(mermaid(snarl, precision)),
+            Format::Text => super::dsl::export(snarl),
+        }
+    }
+}
+
+/// Returns nodes ordered so that each node comes after every node wired into it,
+/// or `None` if the graph has a cycle.
+pub fn topological_order(snarl: &Snarl<Nodes>) -> Option<Vec<NodeId>> {
+    let mut incoming = snarl
+        .node_ids()
+        .map(|(node, _)| (node, 0))
+        .collect::<HashMap<_, usize>>();
+    for (_, to) in snarl.wires() {
+        *incoming.get_mut(&to.node).unwrap() += 1;
+    }
+
+    let mut ready = incoming
+        .iter()
+        .filter(|&(_, &count)| count == 0)
+        .map(|(&node, _)| node)
+        .collect::<Vec<_>>();
+    let mut order = Vec::with_capacity(incoming.len());
+
+    // Pop the lowest id first to keep the order stable between runs.
+    ready.sort_unstable_by(|a, b| b.cmp(a));
+    while let Some(node) = ready.pop() {
+        order.push(node);
+        for (from, to) in snarl.wires() {
+            if from.node != node {
+                continue;
+            }
+            let count = incoming.get_mut(&to.node).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push(to.node);
+                ready.sort_unstable_by(|a, b| b.cmp(a));
+            }
+        }
+    }
+
+    (order.len() == incoming.len()).then_some(order)
+}
+
+/// Returns the nodes wired, directly or not, into any Sink.
+fn sink_upstream(snarl: &Snarl<Nodes>) -> HashSet<NodeId> {
+    let mut stack = snarl
+        .node_ids()
+        .filter(|(_, node)| matches!(node, Nodes::Sink))
+        .map(|(node, _)| node)
+        .collect::<Vec<_>>();
+    let mut upstream = HashSet::new();
+
+    while let Some(node) = stack.pop() {
+        if upstream.insert(node) {
+            stack.extend(
+                snarl
+                    .wires()
+                    .filter(|(_, to)| to.node == node)
+                    .map(|(from, _)| from.node),
+            );
+        }
+    }
+
+    upstream
+}
+
+/// Allocates unique identifiers.
+#[derive(Default)]
+struct Names(HashSet<String>);
+
+impl Names {
+    fn unique(&mut self, base: &str) -> String {
+        let mut name = base.to_owned();
+        let mut suffix = 0;
+        while !self.0.insert(name.clone()) {
+            suffix += 1;
+            name = format!("{base}_{suffix}");
+        }
+        name
+    }
+}
+
+/// Generates a Rust function computing every Sink of the graph.
+///
+/// Number and Slider nodes and unconnected Expr bindings become parameters,
+/// and connected Sinks are returned in graph order. Slider parameters are clamped to their range.
+pub fn rust(snarl: &Snarl<Nodes>) -> Result<String, String> {
+    let order = topological_order(snarl).ok_or("Graph has a cycle")?;
+    let upstream = sink_upstream(snarl);
+
+    let mut names = Names::default();
+    let mut params = Vec::new();
+    let mut body = String::new();
+    let mut outputs = Vec::new();
+
+    // Rust value and type of each node's output.
+    let mut values = HashMap::<NodeId, (String, &str)>::new();
+
+    for node in order.into_iter().filter(|node| upstream.contains(node)) {
+        match snarl[node] {
+            Nodes::Sink => {
+                if let [remote] = *snarl.in_pin(InPinId { node, input: 0 }).remotes {
+                    outputs.push(values[&remote.node].clone());
+                }
+            }
+            Nodes::Number(ref number_node) => {
+                let name = names.unique(&format!("number_{}", node.0));
+                let mut doc = format!("{:?}", number_node.value);
+                if !number_node.unit.is_empty() {
+                    let _ = write!(doc, ", in {}", number_node.unit);
+                }
+                params.push((name.clone(), doc));
+                values.insert(node, (name, "f64"));
+            }
+            Nodes::Slider(ref slider_node) => {
+                let name = names.unique(&format!("slider_{}", node.0));
+                let (low, high) = (
+                    slider_node.min.min(slider_node.max),
+                    slider_node.min.max(slider_node.max),
+                );
+                let mut doc = format!("{:?}, from {low:?} to {high:?}", slider_node.value_out());
+                if slider_node.step > 0.0 {
+                    let _ = write!(doc, " in steps of {:?}", slider_node.step);
+                }
+                if !slider_node.unit.is_empty() {
+                    let _ = write!(doc, ", in {}", slider_node.unit);
+                }
+                params.push((name.clone(), doc));
+                let _ = writeln!(body, "    let {name} = {name}.clamp({low:?}, {high:?});");
+                values.insert(node, (name, "f64"));
+            }
+            Nodes::String(ref value) => {
+                values.insert(node, (format!("{value:?}"), "&'static str"));
+            }
+            Nodes::ShowImage(ref show_image) => {
+                // Size and load status are only known to the UI.
+                let info_wired = (1..NodeViewer::default().outputs(&snarl[node]))
+                    .any(|output| !snarl.out_pin(OutPinId { node, output }).remotes.is_empty());
+                if info_wired {
+                    return Err("Show Image size and status can't be exported to Rust".to_owned());
+                }
+                values.insert(node, (format!("{:?}", show_image.uri), "&'static str"));
+            }
+            Nodes::ExprNode(ref expr_node) => {
+                if expr_node.expr.is_complex() {
+                    return Err("Complex expressions can't be exported to Rust".to_owned());
+                }
+                if expr_node.expr.outputs().len() > 1 {
+                    return Err(
+                        "Expr nodes with several outputs can't be exported to Rust".to_owned()
+                    );
+                }
+                let mut substitutions = HashMap::new();
+                for (idx, binding) in expr_node.bindings.iter().enumerate() {
+                    let pin = snarl.in_pin(InPinId {
+                        node,
+                        input: idx + 1,
+                    });
+                    let value = match *pin.remotes {
+                        [remote] => {
+                            let value = Expr::Var(values[&remote.node].0.clone());
+                            // Expressions work in base units, Numbers and Sliders in their own.
+                            let unit = match snarl[remote.node] {
+                                Nodes::Number(ref number_node) => number_node.unit.as_str(),
+                                Nodes::Slider(ref slider_node) => slider_node.unit.as_str(),
+                                _ => "",
+                            };
+                            match units::unit(unit) {
+                                Some((scale, _)) if scale != 1.0 => Expr::BinOp {
+                                    lhs: Box::new(value),
+                                    op: BinOp::Mul,
+                                    rhs: Box::new(Expr::Val(scale)),
+                                },
+                                _ => value,
+                            }
+                        }
+                        _ => {
+                            let name = names.unique(binding);
+                            params.push((name.clone(), format!("{:?}", expr_node.values[idx])));
+                            Expr::Var(name)
+                        }
+                    };
+                    substitutions.insert(binding.as_str(), value);
+                }
+
+                let name = names.unique(&format!("expr_{}", node.0));
+                let expr = expr_node.expr.substitute(&substitutions);
+                let _ = writeln!(body, "    let {name} = {};", RustExpr(&expr));
+                values.insert(node, (name, "f64"));
+            }
+            ref other @ (Nodes::Interval(_)
+            | Nodes::StringOp(_)
+            | Nodes::Regex(_)
+            | Nodes::Toggle(_)
+            | Nodes::Logic(_)
+            | Nodes::Color(_)
+            | Nodes::Vector(_)
+            | Nodes::List(_)
+            | Nodes::Table(_)
+            | Nodes::Json(_)
+            | Nodes::Image(_)
+            | Nodes::Time(_)
+            | Nodes::Keyframes(_)
+            | Nodes::Random(_)
+            | Nodes::Complex(_)
+            | Nodes::Script(_)
+            | Nodes::Custom(_)) => {
+                return Err(format!(
+                    "{} nodes can't be exported to Rust",
+                    NodeViewer::default().title(other)
+                ));
+            }
+        }
+    }
+
+    let mut code = String::from("/// Generated from a node graph.\n");
+    if !params.is_empty() {
+        code.push_str("///\n/// Parameter values in the graph:\n");
+        for (name, value) in &params {
+            let _ = writeln!(code, "/// - `{name}`: {value}");
+        }
+    }
+
+    let params = params
+        .iter()
+        .map(|(name, _)| format!("{name}: f64"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let _ = write!(code, "pub fn graph({params})");
+
+    match &*outputs {
+        [] => {}
+        [(_, ty)] => {
+            let _ = write!(code, " -> {ty}");
+        }
+        outputs => {
+            let types = outputs.iter().map(|(_, ty)| *ty).collect::<Vec<_>>();
+            let _ = write!(code, " -> ({})", types.join(", "));
+        }
+    }
+
+    code.push_str(" {\n");
+    code.push_str(&body);
+    match &*outputs {
+        [] => {}
+        [(value, _)] => {
+            let _ = writeln!(code, "    {value}");
+        }
+        outputs => {
+            let values = outputs
+                .iter()
+                .map(|(value, _)| &**value)
+                .collect::<Vec<_>>();
+            let _ = writeln!(code, "    ({})", values.join(", "));
+        }
+    }
+    code.push_str("}\n");
+
+    Ok(code)
+}
+
+fn hex(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Lines of the node label, its title followed by the current value with `precision` decimals.
+fn node_label(snarl: &Snarl<Nodes>, node: NodeId, precision: u8) -> [String; 2] {
+    let value = match snarl[node] {
+        Nodes::Sink => match *snarl.in_pin(InPinId { node, input: 0 }).remotes {
+            [remote] => snarl[remote.node].out_text(remote.output, precision),
+            _ => "None".to_owned(),
+        },
+        Nodes::ExprNode(ref expr_node) => {
+            format!(
+                "{} = {}",
+                expr_node.text,
+                snarl[node].out_text(0, precision)
+            )
+        }
+        Nodes::Regex(ref regex_node) => format!("{:?}", regex_node.pattern),
+        Nodes::Table(ref table_node) => table_node.columns.join(", "),
+        Nodes::Script(ref script_node) => {
+            let outs = script_node.outs.iter().map(|port| port.name.as_str());
+            outs.collect::<Vec<_>>().join(", ")
+        }
+        Nodes::Custom(ref custom_node) => match custom_node.outs.len() {
+            0 => String::new(),
+            _ => snarl[node].out_text(0, precision),
+        },
+        ref value => value.out_text(0, precision),
+    };
+
+    [NodeViewer::default().title(&snarl[node]), value]
+}
+
+/// Returns wires with the name of the input pin, `None` for the input of a Sink.
+fn labelled_wires(snarl: &Snarl<Nodes>) -> Vec<(NodeId, NodeId, Option<&str>, Color32)> {
+    let mut wires = snarl.wires().collect::<Vec<_>>();
+    wires.sort_unstable();
+
+    wires
+        .into_iter()
+        .map(|(from, to)| {
+            let label = match snarl[to.node] {
+                Nodes::Sink => None,
+                ref node => Some(node.label_in(to.input)),
+            };
+            (
+                from.node,
+                to.node,
+                label,
+                snarl[from.node].out_color(from.output),
+            )
+        })
+        .collect()
+}
+
+/// Writes the graph in Graphviz DOT language.
+pub fn dot(snarl: &Snarl<Nodes>, precision: u8) -> String {
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+
+    let mut code =
+        String::from("digraph nodes {\n    rankdir=LR;\n    node [shape=box, style=rounded];\n\n");
+
+    for (node, value) in snarl.node_ids() {
+        let [title, value_text] = node_label(snarl, node, precision);
+        let _ = writeln!(
+            code,
+            "    n{} [label=\"{}\\n{}\", color=\"{}\"];",
+            node.0,
+            escape(&title),
+            escape(&value_text),
+            hex(value.out_color(0)),
+        );
+    }
+
+    code.push('\n');
+    for (from, to, label, color) in labelled_wires(snarl) {
+        let color = hex(color);
+        let _ = write!(code, "    n{} -> n{} [color=\"{color}\"", from.0, to.0);
+        if let Some(label) = label {
+            let _ = write!(code, ", label=\"{}\", fontcolor=\"{color}\"", escape(label));
+        }
+        code.push_str("];\n");
+    }
+
+    code.push_str("}\n");
+    code
+}
+
+/// Writes the graph as Mermaid flowchart.
+pub fn mermaid(snarl: &Snarl<Nodes>, precision: u8) -> String {
+    fn escape(text: &str) -> String {
+        text.replace('"', "#quot;")
+            .replace('<', "#lt;")
+            .replace('>', "#gt;")
+    }
+
+    let mut code = String::from("flowchart LR\n");
+    let mut styles = String::new();
+
+    for (node, value) in snarl.node_ids() {
+        let [title, value_text] = node_label(snarl, node, precision);
+        let _ = writeln!(
+            code,
+            "    n{}[\"{}<br/>{}\"]",
+            node.0,
+            escape(&title),
+            escape(&value_text),
+        );
+        let _ = writeln!(
+            styles,
+            "    style n{} stroke:{}",
+            node.0,
+            hex(value.out_color(0))
+        );
+    }
+
+    for (idx, (from, to, label, color)) in labelled_wires(snarl).into_iter().enumerate() {
+        match label {
+            Some(label) => {
+                let _ = writeln!(code, "    n{} -->|\"{}\"| n{}", from.0, escape(label), to.0);
+            }
+            None => {
+                let _ = writeln!(code, "    n{} --> n{}", from.0, to.0);
+            }
+        }
+        let _ = writeln!(styles, "    linkStyle {idx} stroke:{}", hex(color));
+    }
+
+    code.push_str(&styles);
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Pos2;
+
+    use super::super::{number::NumberNode, slider::SliderNode, ExprNode};
+    use super::*;
+
+    /// Inserts an Expr node for `text` and wires `sources` into its bindings in order.
+    fn expr(snarl: &mut Snarl<Nodes>, text: &str, sources: &[NodeId]) -> NodeId {
+        let expr_node = ExprNode::with_text(text.to_owned()).unwrap();
+        let node = snarl.insert_node(Pos2::ZERO, Nodes::ExprNode(expr_node));
+        for (idx, &source) in sources.iter().enumerate() {
+            snarl.connect(
+                OutPinId {
+                    node: source,
+                    output: 0,
+                },
+                InPinId {
+                    node,
+                    input: idx + 1,
+                },
+            );
+        }
+        node
+    }
+
+    fn sink(snarl: &mut Snarl<Nodes>, source: NodeId) {
+        let node = snarl.insert_node(Pos2::ZERO, Nodes::Sink);
+        snarl.connect(
+            OutPinId {
+                node: source,
+                output: 0,
+            },
+            InPinId { node, input: 0 },
+        );
+    }
+
+    #[test]
+    fn exports_valid_rust() {
+        let mut snarl = Snarl::new();
+        let number = snarl.insert_node(Pos2::ZERO, Nodes::Number(NumberNode::new(2.5)));
+        let expr_node = expr(&mut snarl, "x * y + 1", &[number]);
+        sink(&mut snarl, expr_node);
+
+        let code = rust(&snarl).unwrap();
+        syn::parse_file(&code).unwrap();
+        assert!(code.contains("pub fn graph(number_0: f64, y: f64) -> f64"));
+    }
+
+    #[test]
+    fn scales_numbers_to_base_units() {
+        let mut snarl = Snarl::new();
+        let mut number_node = NumberNode::new(3.0);
+        number_node.unit = "km".to_owned();
+        let number = snarl.insert_node(Pos2::ZERO, Nodes::Number(number_node));
+        let expr_node = expr(&mut snarl, "x + y", &[number]);
+        sink(&mut snarl, expr_node);
+
+        let code = rust(&snarl).unwrap();
+        syn::parse_file(&code).unwrap();
+        assert!(code.contains("3.0, in km"));
+        assert!(code.contains("number_0 * 1000.0"));
+    }
+
+    #[test]
+    fn clamps_sliders() {
+        let mut snarl = Snarl::new();
+        let mut slider_node = SliderNode::new();
+        slider_node.min = 5.0;
+        slider_node.max = -5.0;
+        slider_node.unit = "km".to_owned();
+        let slider = snarl.insert_node(Pos2::ZERO, Nodes::Slider(slider_node));
+        let expr_node = expr(&mut snarl, "x / 2", &[slider]);
+        sink(&mut snarl, expr_node);
+        sink(&mut snarl, slider);
+
+        let code = rust(&snarl).unwrap();
+        syn::parse_file(&code).unwrap();
+        assert!(code.contains("let slider_0 = slider_0.clamp(-5.0, 5.0);"));
+        // Kilometres are scaled to metres before entering the expression.
+        assert!(code.contains("slider_0 * 1000.0"));
+        assert!(code.contains("-> (f64, f64)"));
+    }
+
+    #[test]
+    fn rounds_labels_to_precision() {
+        let mut snarl = Snarl::new();
+        let number = snarl.insert_node(Pos2::ZERO, Nodes::Number(NumberNode::new(1.23456)));
+        sink(&mut snarl, number);
+
+        let code = dot(&snarl, 1);
+        assert!(code.contains("Number\\n1.2\""));
+        let code = mermaid(&snarl, 4);
+        assert!(code.contains("1.2346"));
+    }
+
+    #[test]
+    fn rejects_unsupported_exprs() {
+        let mut snarl = Snarl::new();
+        let complex = expr(&mut snarl, "2 + 3i", &[]);
+        sink(&mut snarl, complex);
+        assert_eq!(
+            rust(&snarl).unwrap_err(),
+            "Complex expressions can't be exported to Rust"
+        );
+
+        let mut snarl = Snarl::new();
+        let several = expr(&mut snarl, "a = x; b = x * 2", &[]);
+        sink(&mut snarl, several);
+        assert_eq!(
+            rust(&snarl).unwrap_err(),
+            "Expr nodes with several outputs can't be exported to Rust"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Nodes made from files dropped on the canvas, and loading of `data:` URIs.
+
+use base64::Engine as _;
+use egui::load::{Bytes, BytesLoadResult, BytesLoader, BytesPoll, LoadError};
+
+use super::{
+    image::ShowImageNode,
+    table::{Header, TableNode},
+    Nodes,
+};
+
+/// Extensions of files shown as images when the platform gives no mime type.
+const IMAGE_EXTENSIONS: [&str; 8] = ["png", "jpg", "jpeg", "gif", "bmp", "webp", "svg", "ico"];
+
+/// Node for a dropped file, Show Image for images, Table for CSV and String for other text.
+///
+/// Images on disk are referred to by path, others are embedded as `data:` URIs
+/// so that they are saved with the graph.
+pub fn dropped_node(file: &egui::DroppedFile) -> Result<Nodes, String> {
+    let name = match file.path {
+        Some(ref path) => path.display().to_string(),
+        None => file.name.clone(),
+    };
+    let extension = name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+    let image = file.mime.starts_with("image/") || IMAGE_EXTENSIONS.contains(&&*extension);
+
+    if let (true, Some(path)) = (image, &file.path) {
+        let uri = format!("file://{}", path.display());
+        return Ok(Nodes::ShowImage(ShowImageNode::new(uri)));
+    }
+
+    let bytes = match (&file.bytes, &file.path) {
+        (Some(bytes), _) => bytes.to_vec(),
+        (None, Some(path)) => {
+            std::fs::read(path).map_err(|err| format!("Failed to read {name}: {err}"))?
+        }
+        (None, None) => return Err(format!("{name} has no contents")),
+    };
+
+    if image {
+        let mime = match (&*file.mime, &*extension) {
+            ("", "jpg") => "image/jpeg".to_owned(),
+            ("", "svg") => "image/svg+xml".to_owned(),
+            ("", extension) => format!("image/{extension}"),
+            (mime, _) => mime.to_owned(),
+        };
+        let data = base64::engine::general_purpose::STANDARD.encode(bytes);
+        let uri = format!("data:{mime};base64,{data}");
+        return Ok(Nodes::ShowImage(ShowImageNode::new(uri)));
+    }
+
+    let text =
+        String::from_utf8(bytes).map_err(|_| format!("{name} is neither an image nor text"))?;
+    Ok(match &*extension {
+        "csv" => Nodes::Table(TableNode::with_text(text, b',', Header::Auto)),
+        "tsv" => Nodes::Table(TableNode::with_text(text, b'\t', Header::Auto)),
+        _ => Nodes::String(text),
+    })
+}
+
+/// Loads bytes embedded in `data:` URIs, either base64 or percent-encoded.
+pub struct DataUriLoader;
+
+impl BytesLoader for DataUriLoader {
+    fn id(&self) -> &str {
+        egui::generate_loader_id!(DataUriLoader)
+    }
+
+    fn load(&self, _ctx: &egui::Context, uri: &str) -> BytesLoadResult {
+        let Some(data) = uri.strip_prefix("data:") else {
+            return Err(LoadError::NotSupported);
+        };
+        let (header, payload) = data
+            .split_once(',')
+            .ok_or_else(|| LoadError::Loading("Expected `,` in data URI".to_owned()))?;
+
+        let (header, base64) = match header.strip_suffix(";base64") {
+            Some(header) => (header, true),
+            None => (header, false),
+        };
+        let bytes = if base64 {
+            base64::engine::general_purpose::STANDARD
+                .decode(payload.trim())
+                .map_err(|err| LoadError::Loading(err.to_string()))?
+        } else {
+            percent_decode(payload)
+        };
+
+        let mime = header.split(';').next().unwrap_or_default();
+        Ok(BytesPoll::Ready {
+            size: None,
+            bytes: Bytes::Shared(bytes.into()),
+            mime: (!mime.is_empty()).then(|| mime.to_owned()),
+        })
+    }
+
+    fn forget(&self, _uri: &str) {}
+
+    fn forget_all(&self) {}
+
+    fn byte_size(&self) -> usize {
+        0
+    }
+}
+
+/// Decodes `%xx` escapes, leaving invalid ones as written.
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| bytes.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    decoded
+}
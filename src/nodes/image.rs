@@ -0,0 +1,429 @@
+//! Image loading and processing nodes, working on decoded pixels on the CPU.
+
+use std::cell::RefCell;
+use std::sync::{Arc, OnceLock};
+
+use ::image::{imageops, Rgba, RgbaImage};
+
+use super::{PinCompat, Value, PIN_BOOL, PIN_IMG, PIN_NUM, PIN_STR};
+
+/// Outputs of ShowImage after the URL, reporting what egui's loaders found.
+pub const INFO_OUTPUTS: [&str; 5] = ["width", "height", "format", "loaded", "error"];
+
+/// Largest width and height nodes resize or crop to.
+const MAX_SIZE: f64 = 8192.0;
+
+/// Decoded RGBA image, shared between the nodes that pass it on.
+///
+/// Pixels aren't saved with the graph, they are pulled again through the wires.
+#[derive(Clone, Default)]
+pub struct Image(Arc<ImageData>);
+
+#[derive(Default)]
+struct ImageData {
+    pixels: RgbaImage,
+    texture: OnceLock<egui::TextureHandle>,
+}
+
+impl Image {
+    fn new(pixels: RgbaImage) -> Self {
+        Image(Arc::new(ImageData {
+            pixels,
+            texture: OnceLock::new(),
+        }))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.0.pixels.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.0.pixels.height()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width() == 0 || self.height() == 0
+    }
+
+    /// Shows the image scaled to fit `max_size`.
+    pub fn show(&self, ui: &mut egui::Ui, max_size: f32) {
+        if self.is_empty() {
+            ui.weak("No image");
+            return;
+        }
+
+        let texture = self.0.texture.get_or_init(|| {
+            let size = [self.width() as usize, self.height() as usize];
+            let image = egui::ColorImage::from_rgba_unmultiplied(size, self.0.pixels.as_raw());
+            ui.ctx()
+                .load_texture("image", image, egui::TextureOptions::LINEAR)
+        });
+        ui.add(egui::Image::new(texture).max_size(egui::Vec2::splat(max_size)));
+    }
+}
+
+/// Images are the same if they share pixels or are both empty.
+impl PartialEq for Image {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || (self.is_empty() && other.is_empty())
+    }
+}
+
+impl serde::Serialize for Image {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit()
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Image {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <()>::deserialize(deserializer)?;
+        Ok(Image::default())
+    }
+}
+
+/// URL of an image shown in the node, with what is known about it once loaded.
+#[derive(Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct ShowImageNode {
+    pub(super) uri: String,
+    #[serde(skip)]
+    info: ImageInfo,
+}
+
+/// Load result of the URL it was polled for.
+#[derive(Clone, Default)]
+struct ImageInfo {
+    uri: String,
+    size: Option<[usize; 2]>,
+    format: String,
+    error: Option<String>,
+}
+
+impl ShowImageNode {
+    pub fn new(uri: String) -> Self {
+        ShowImageNode {
+            uri,
+            info: ImageInfo::default(),
+        }
+    }
+
+    pub(super) fn out_compat(idx: usize) -> PinCompat {
+        match idx {
+            0 => PIN_IMG,
+            1 | 2 => PIN_NUM,
+            4 => PIN_BOOL,
+            _ => PIN_STR,
+        }
+    }
+
+    pub(super) fn label_out(idx: usize) -> &'static str {
+        match idx {
+            0 => "",
+            _ => INFO_OUTPUTS[idx - 1],
+        }
+    }
+
+    /// Asks egui's loaders about the URL until it is loaded or fails.
+    pub(super) fn poll(&mut self, ctx: &egui::Context) {
+        if self.info.uri != self.uri {
+            self.info = ImageInfo {
+                uri: self.uri.clone(),
+                ..ImageInfo::default()
+            };
+        }
+        if self.uri.is_empty() || self.info.size.is_some() || self.info.error.is_some() {
+            return;
+        }
+
+        match ctx.try_load_image(&self.uri, egui::SizeHint::default()) {
+            Ok(egui::load::ImagePoll::Ready { image }) => self.info.size = Some(image.size),
+            Ok(egui::load::ImagePoll::Pending { .. }) => return,
+            Err(err) => {
+                self.info.error = Some(err.to_string());
+                return;
+            }
+        }
+        if let Ok(egui::load::BytesPoll::Ready { bytes, mime, .. }) = ctx.try_load_bytes(&self.uri)
+        {
+            self.info.format = match ::image::guess_format(&bytes) {
+                Ok(format) => format.extensions_str()[0].to_owned(),
+                Err(_) => mime
+                    .as_deref()
+                    .and_then(|mime| mime.split('/').nth(1))
+                    .unwrap_or_default()
+                    .to_owned(),
+            };
+        }
+    }
+
+    /// URL or what is known about it, zero and empty until loaded.
+    pub(super) fn value_out(&self, idx: usize) -> Value {
+        let polled = self.info.uri == self.uri;
+        let size = self.info.size.filter(|_| polled);
+        let [width, height] = size.unwrap_or_default();
+        #[allow(clippy::cast_precision_loss)]
+        match idx {
+            0 => Value::String(self.uri.clone()),
+            1 => Value::Number(width as f64),
+            2 => Value::Number(height as f64),
+            3 if polled => Value::String(self.info.format.clone()),
+            4 => Value::Bool(size.is_some()),
+            5 if polled => Value::String(self.info.error.clone().unwrap_or_default()),
+            _ => Value::String(String::new()),
+        }
+    }
+}
+
+node_ops! {
+    pub enum ImageOp {
+        Load as "LoadImage": "Load Image", "Loads and decodes image from the URL";
+        Resize: "Resize", "Scales image to the width and height";
+        Crop: "Crop", "Cuts out the rectangle of the image";
+        /// Rotates clockwise by quarter turns.
+        Rotate: "Rotate", "Rotates image clockwise by quarter turns";
+        Flip: "Flip", "Mirrors image horizontally, or vertically if checked";
+        Grayscale: "Grayscale", "Converts image to shades of gray";
+        BrightnessContrast: "Brightness/Contrast", "Adjusts brightness and contrast, both in -1..1";
+        Blur: "Blur", "Blurs image with the Gaussian sigma in pixels";
+        Threshold: "Threshold", "Turns pixels white if their luminance is at least the level";
+        Blend: "Blend", "Mixes B over A, resizing B to the size of A";
+    }
+}
+
+impl ImageOp {
+    /// Labels and initial values of the inputs.
+    fn inputs(self) -> Vec<(&'static str, Value)> {
+        let image = || ("Image", Value::Image(Image::default()));
+        match self {
+            ImageOp::Load => vec![("URL", Value::String(String::new()))],
+            ImageOp::Resize => vec![
+                image(),
+                ("Width", Value::Number(256.0)),
+                ("Height", Value::Number(256.0)),
+            ],
+            ImageOp::Crop => vec![
+                image(),
+                ("X", Value::Number(0.0)),
+                ("Y", Value::Number(0.0)),
+                ("Width", Value::Number(256.0)),
+                ("Height", Value::Number(256.0)),
+            ],
+            ImageOp::Rotate => vec![image(), ("Turns", Value::Number(1.0))],
+            ImageOp::Flip => vec![image(), ("Vertical", Value::Bool(false))],
+            ImageOp::Grayscale => vec![image()],
+            ImageOp::BrightnessContrast => vec![
+                image(),
+                ("Brightness", Value::Number(0.0)),
+                ("Contrast", Value::Number(0.0)),
+            ],
+            ImageOp::Blur => vec![image(), ("Sigma", Value::Number(2.0))],
+            ImageOp::Threshold => vec![image(), ("Level", Value::Number(0.5))],
+            ImageOp::Blend => vec![
+                ("A", Value::Image(Image::default())),
+                ("B", Value::Image(Image::default())),
+                ("Mix", Value::Number(0.5)),
+            ],
+        }
+    }
+}
+
+/// Inputs of the last result, which is reused while they stay the same.
+type Cached = (Vec<Value>, Result<Image, String>);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ImageNode {
+    pub(super) op: ImageOp,
+    /// Source images followed by parameters such as Width or Sigma, editable while unwired.
+    pub(super) inputs: Vec<Value>,
+    #[serde(skip)]
+    cache: RefCell<Option<Cached>>,
+}
+
+impl ImageNode {
+    pub fn new(op: ImageOp) -> Self {
+        ImageNode {
+            op,
+            inputs: op.inputs().into_iter().map(|(_, value)| value).collect(),
+            cache: RefCell::new(None),
+        }
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        self.op.inputs()[idx].0
+    }
+
+    /// Load also takes the URL of a ShowImage node.
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        match self.op {
+            ImageOp::Load => PIN_STR | PIN_IMG,
+            _ => self.inputs[idx].compat(),
+        }
+    }
+
+    /// Starts loading the URL if it changed, and decodes it once loaded.
+    pub(super) fn poll(&self, ctx: &egui::Context) {
+        let uri = self.inputs[0].string();
+        if self.op != ImageOp::Load || uri.is_empty() || self.cached().is_some() {
+            return;
+        }
+
+        let result = match ctx.try_load_bytes(&uri) {
+            Ok(egui::load::BytesPoll::Pending { .. }) => return,
+            Ok(egui::load::BytesPoll::Ready { bytes, .. }) => ::image::load_from_memory(&bytes)
+                .map(|image| Image::new(image.into_rgba8()))
+                .map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        *self.cache.borrow_mut() = Some((self.inputs.clone(), result));
+    }
+
+    /// Result for the current inputs if it is cached.
+    fn cached(&self) -> Option<Result<Image, String>> {
+        match *self.cache.borrow() {
+            Some((ref inputs, ref result)) if *inputs == self.inputs => Some(result.clone()),
+            _ => None,
+        }
+    }
+
+    /// Load error.
+    pub(super) fn error(&self) -> Option<String> {
+        self.cached().and_then(Result::err)
+    }
+
+    pub(super) fn value_out(&self) -> Value {
+        let image = match self.cached() {
+            Some(result) => result.unwrap_or_default(),
+            None if self.op == ImageOp::Load => Image::default(),
+            None => {
+                let image = self.process();
+                *self.cache.borrow_mut() = Some((self.inputs.clone(), Ok(image.clone())));
+                image
+            }
+        };
+        Value::Image(image)
+    }
+
+    fn process(&self) -> Image {
+        let Value::Image(ref source) = self.inputs[0] else {
+            return Image::default();
+        };
+        if source.is_empty() {
+            return Image::default();
+        }
+
+        let pixels = &source.0.pixels;
+        let number_in = |idx: usize| self.inputs[idx].number();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let size_in =
+            |idx: usize, max: u32| number_in(idx).round().clamp(0.0, f64::from(max)) as u32;
+
+        let processed = match self.op {
+            ImageOp::Load => unreachable!("Load has no image input"),
+            ImageOp::Resize => {
+                let width = size_in(1, MAX_SIZE as u32).max(1);
+                let height = size_in(2, MAX_SIZE as u32).max(1);
+                imageops::resize(pixels, width, height, imageops::FilterType::Triangle)
+            }
+            ImageOp::Crop => {
+                let x = size_in(1, pixels.width() - 1);
+                let y = size_in(2, pixels.height() - 1);
+                let width = size_in(3, pixels.width() - x).max(1);
+                let height = size_in(4, pixels.height() - y).max(1);
+                imageops::crop_imm(pixels, x, y, width, height).to_image()
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            ImageOp::Rotate => match (number_in(1).round() as i64).rem_euclid(4) {
+                1 => imageops::rotate90(pixels),
+                2 => imageops::rotate180(pixels),
+                3 => imageops::rotate270(pixels),
+                _ => pixels.clone(),
+            },
+            ImageOp::Flip => {
+                if self.inputs[1].bool() {
+                    imageops::flip_vertical(pixels)
+                } else {
+                    imageops::flip_horizontal(pixels)
+                }
+            }
+            ImageOp::Grayscale => map_pixels(pixels, |pixel| {
+                let luma = luminance(pixel);
+                [luma, luma, luma]
+            }),
+            ImageOp::BrightnessContrast => {
+                let brightness = number_in(1) as f32;
+                let contrast = 1.0 + number_in(2) as f32;
+                map_pixels(pixels, |pixel| {
+                    [0, 1, 2].map(|channel| {
+                        (f32::from(pixel[channel]) / 255.0 - 0.5) * contrast + 0.5 + brightness
+                    })
+                })
+            }
+            ImageOp::Blur => {
+                let sigma = number_in(1) as f32;
+                if sigma > 0.0 {
+                    imageops::blur(pixels, sigma)
+                } else {
+                    pixels.clone()
+                }
+            }
+            ImageOp::Threshold => {
+                let level = number_in(1) as f32;
+                map_pixels(pixels, |pixel| {
+                    let value = if luminance(pixel) >= level { 1.0 } else { 0.0 };
+                    [value; 3]
+                })
+            }
+            ImageOp::Blend => {
+                let Value::Image(ref other) = self.inputs[1] else {
+                    return source.clone();
+                };
+                if other.is_empty() {
+                    return source.clone();
+                }
+                let other = if other.0.pixels.dimensions() == pixels.dimensions() {
+                    other.0.pixels.clone()
+                } else {
+                    imageops::resize(
+                        &other.0.pixels,
+                        pixels.width(),
+                        pixels.height(),
+                        imageops::FilterType::Triangle,
+                    )
+                };
+                let mix = number_in(2).clamp(0.0, 1.0) as f32;
+                let mut blended = pixels.clone();
+                for (pixel, other) in blended.pixels_mut().zip(other.pixels()) {
+                    for channel in 0..4 {
+                        let a = f32::from(pixel[channel]);
+                        let b = f32::from(other[channel]);
+                        pixel[channel] = to_u8((a + (b - a) * mix) / 255.0);
+                    }
+                }
+                blended
+            }
+        };
+        Image::new(processed)
+    }
+}
+
+/// Luminance in 0..1 of sRGB components.
+fn luminance(pixel: &Rgba<u8>) -> f32 {
+    (0.2126 * f32::from(pixel[0]) + 0.7152 * f32::from(pixel[1]) + 0.0722 * f32::from(pixel[2]))
+        / 255.0
+}
+
+/// Replaces color of each pixel with components in 0..1, keeping alpha.
+fn map_pixels(pixels: &RgbaImage, f: impl Fn(&Rgba<u8>) -> [f32; 3]) -> RgbaImage {
+    let mut mapped = pixels.clone();
+    for pixel in mapped.pixels_mut() {
+        let [r, g, b] = f(pixel).map(to_u8);
+        *pixel = Rgba([r, g, b, pixel[3]]);
+    }
+    mapped
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_u8(component: f32) -> u8 {
+    (component.clamp(0.0, 1.0) * 255.0).round() as u8
+}
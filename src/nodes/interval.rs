@@ -0,0 +1,141 @@
+//! Values known only within bounds, and their arithmetic.
+
+/// Closed range of values, `lo` to `hi`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bounds {
+    pub lo: f64,
+    pub hi: f64,
+}
+
+impl Bounds {
+    pub const fn point(value: f64) -> Self {
+        Bounds {
+            lo: value,
+            hi: value,
+        }
+    }
+
+    pub fn add(self, other: Bounds) -> Bounds {
+        Bounds {
+            lo: self.lo + other.lo,
+            hi: self.hi + other.hi,
+        }
+    }
+
+    pub fn neg(self) -> Bounds {
+        Bounds {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+
+    /// Bounds of the distance from zero.
+    pub fn abs(self) -> Bounds {
+        if self.lo >= 0.0 {
+            self
+        } else if self.hi <= 0.0 {
+            self.neg()
+        } else {
+            Bounds {
+                lo: 0.0,
+                hi: self.hi.max(-self.lo),
+            }
+        }
+    }
+
+    pub fn mul(self, other: Bounds) -> Bounds {
+        Bounds::from_corners([
+            self.lo * other.lo,
+            self.lo * other.hi,
+            self.hi * other.lo,
+            self.hi * other.hi,
+        ])
+    }
+
+    /// Divides by bounds that don't contain zero, not even just zero, since the quotient would be
+    /// unbounded.
+    pub fn div(self, other: Bounds) -> Result<Bounds, String> {
+        if other.lo <= 0.0 && other.hi >= 0.0 {
+            return Err(format!("Can't divide by {other}, it contains zero"));
+        }
+        Ok(self.mul(Bounds {
+            lo: 1.0 / other.hi,
+            hi: 1.0 / other.lo,
+        }))
+    }
+
+    fn from_corners<const N: usize>(corners: [f64; N]) -> Bounds {
+        Bounds {
+            lo: corners.into_iter().fold(f64::INFINITY, f64::min),
+            hi: corners.into_iter().fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+impl std::fmt::Display for Bounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}, {}]",
+            super::format_float(self.lo, f.precision()),
+            super::format_float(self.hi, f.precision())
+        )
+    }
+}
+
+/// Number with a tolerance, its output carries the bounds into Expr nodes.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct IntervalNode {
+    pub(super) value: f64,
+    pub(super) tolerance: f64,
+}
+
+impl IntervalNode {
+    pub const fn new() -> Self {
+        IntervalNode {
+            value: 0.0,
+            tolerance: 0.1,
+        }
+    }
+
+    pub(super) fn bounds(&self) -> Bounds {
+        let tolerance = self.tolerance.abs();
+        Bounds {
+            lo: self.value - tolerance,
+            hi: self.value + tolerance,
+        }
+    }
+
+    pub(super) fn show_output(&mut self, ui: &mut egui::Ui, precision: u8) {
+        ui.add(egui::DragValue::new(&mut self.value));
+        ui.label("±");
+        ui.add(
+            egui::DragValue::new(&mut self.tolerance)
+                .speed(0.01)
+                .range(0.0..=f64::INFINITY),
+        )
+        .on_hover_text(format!("{:.*}", usize::from(precision), self.bounds()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_divisors_containing_zero() {
+        let bounds = Bounds { lo: 1.0, hi: 2.0 };
+        for divisor in [
+            Bounds { lo: -1.0, hi: 1.0 },
+            Bounds { lo: 0.0, hi: 1.0 },
+            Bounds { lo: -1.0, hi: -0.0 },
+            Bounds::point(0.0),
+        ] {
+            assert!(bounds.div(divisor).is_err(), "{divisor}");
+        }
+        assert_eq!(
+            bounds.div(Bounds { lo: 2.0, hi: 4.0 }),
+            Ok(Bounds { lo: 0.25, hi: 1.0 })
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! JSON parsing and query nodes.
+
+use serde_json::Value as Json;
+
+use super::{PinCompat, Value, PIN_JSON, PIN_STR};
+
+/// Children shown for each array or object in the tree preview.
+const PREVIEW_CHILDREN: usize = 100;
+
+node_ops! {
+    pub enum JsonOp {
+        Parse as "JsonParse": "JSON Parse", "Parses JSON text into a document";
+        /// Selects values with a JSONPath such as `$.items[*].price`.
+        Query as "JsonQuery": "JSON Query",
+            "Selects from the document with a path such as `$.items[*].price`, \
+             outputting numbers, strings, bools and lists as such";
+    }
+}
+
+impl JsonOp {
+    /// Labels and initial values of the inputs.
+    fn inputs(self) -> Vec<(&'static str, Value)> {
+        match self {
+            JsonOp::Parse => vec![("Text", Value::String("{}".to_owned()))],
+            JsonOp::Query => vec![
+                ("JSON", Value::String("{}".to_owned())),
+                ("Path", Value::String("$".to_owned())),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct JsonNode {
+    pub(super) op: JsonOp,
+    /// JSON text or document, then the path for Query.
+    pub(super) inputs: Vec<Value>,
+}
+
+impl JsonNode {
+    pub fn new(op: JsonOp) -> Self {
+        JsonNode {
+            op,
+            inputs: op.inputs().into_iter().map(|(_, value)| value).collect(),
+        }
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        self.op.inputs()[idx].0
+    }
+
+    /// Query takes a document or JSON text.
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        match (self.op, idx) {
+            (JsonOp::Query, 0) => PIN_JSON | PIN_STR,
+            _ => PIN_STR,
+        }
+    }
+
+    /// Query outputs the type of what the path selects.
+    pub(super) fn out_compat(&self) -> PinCompat {
+        match self.op {
+            JsonOp::Parse => PIN_JSON,
+            JsonOp::Query => self.value_out().compat(),
+        }
+    }
+
+    /// Document of Parse, or the one Query selects from.
+    pub(super) fn document(&self) -> Result<Json, String> {
+        match self.inputs[0] {
+            Value::Json(ref json) => Ok(json.clone()),
+            ref text => serde_json::from_str(&text.string()).map_err(|err| err.to_string()),
+        }
+    }
+
+    /// Parse or path error.
+    pub(super) fn error(&self) -> Option<String> {
+        let document = self.document().err();
+        match self.op {
+            JsonOp::Parse => document,
+            JsonOp::Query => document.or_else(|| parse_path(&self.inputs[1].string()).err()),
+        }
+    }
+
+    pub(super) fn value_out(&self) -> Value {
+        let document = self.document().unwrap_or(Json::Null);
+        match self.op {
+            JsonOp::Parse => Value::Json(document),
+            JsonOp::Query => {
+                let Ok((steps, multiple)) = parse_path(&self.inputs[1].string()) else {
+                    return Value::Json(Json::Null);
+                };
+                let selected = select(&steps, &document);
+                match (multiple, selected.as_slice()) {
+                    (false, [json]) => from_json(json),
+                    (false, _) => Value::Json(Json::Null),
+                    (true, _) => list(selected.iter().map(|json| from_json(json)).collect())
+                        .unwrap_or_else(|| {
+                            Value::Json(Json::Array(selected.into_iter().cloned().collect()))
+                        }),
+                }
+            }
+        }
+    }
+}
+
+/// One step of a path.
+enum Step {
+    Key(String),
+    /// Array index, negative ones count from the end.
+    Index(i64),
+    Wildcard,
+}
+
+impl Step {
+    fn select<'a>(&self, json: &'a Json) -> Vec<&'a Json> {
+        match (self, json) {
+            (Step::Key(key), Json::Object(object)) => object.get(key).into_iter().collect(),
+            (&Step::Index(idx), Json::Array(array)) => {
+                let idx = if idx < 0 {
+                    array.len().checked_sub(idx.unsigned_abs() as usize)
+                } else {
+                    usize::try_from(idx).ok()
+                };
+                idx.and_then(|idx| array.get(idx)).into_iter().collect()
+            }
+            (Step::Wildcard, Json::Array(array)) => array.iter().collect(),
+            (Step::Wildcard, Json::Object(object)) => object.values().collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Parses path such as `$.items[0]['name']`, returning its steps and whether it has wildcards.
+fn parse_path(path: &str) -> Result<(Vec<Step>, bool), String> {
+    let mut rest = path.trim();
+    rest = rest.strip_prefix('$').unwrap_or(rest);
+
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            steps.push(match key {
+                "" => return Err(format!("Expected a key after `.` in `{path}`")),
+                "*" => Step::Wildcard,
+                _ => Step::Key(key.to_owned()),
+            });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after
+                .find(']')
+                .ok_or_else(|| format!("Unclosed `[` in `{path}`"))?;
+            let inner = after[..end].trim();
+            let quoted = inner
+                .strip_prefix('\'')
+                .and_then(|inner| inner.strip_suffix('\''))
+                .or_else(|| {
+                    inner
+                        .strip_prefix('"')
+                        .and_then(|inner| inner.strip_suffix('"'))
+                });
+            steps.push(match (inner, quoted) {
+                (_, Some(key)) => Step::Key(key.to_owned()),
+                ("*", None) => Step::Wildcard,
+                (index, None) => Step::Index(
+                    index
+                        .parse()
+                        .map_err(|_| format!("Invalid index `{index}` in `{path}`"))?,
+                ),
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(format!("Expected `.` or `[` in `{path}`"));
+        }
+    }
+
+    let multiple = steps.iter().any(|step| matches!(step, Step::Wildcard));
+    Ok((steps, multiple))
+}
+
+/// Values the path selects from the document.
+fn select<'a>(steps: &[Step], document: &'a Json) -> Vec<&'a Json> {
+    let mut selected = vec![document];
+    for step in steps {
+        selected = selected
+            .into_iter()
+            .flat_map(|json| step.select(json))
+            .collect();
+    }
+    selected
+}
+
+/// List of the values, if they all have the same type.
+fn list(items: Vec<Value>) -> Option<Value> {
+    let same = items
+        .windows(2)
+        .all(|pair| pair[0].compat() == pair[1].compat());
+    same.then_some(Value::List(items))
+}
+
+/// Converts numbers, strings, bools and arrays of the same type to values, others stay documents.
+fn from_json(json: &Json) -> Value {
+    match *json {
+        Json::Number(ref number) => Value::Number(number.as_f64().unwrap_or(f64::NAN)),
+        Json::String(ref value) => Value::String(value.clone()),
+        Json::Bool(value) => Value::Bool(value),
+        Json::Array(ref array) => {
+            list(array.iter().map(from_json).collect()).unwrap_or_else(|| Value::Json(json.clone()))
+        }
+        Json::Null | Json::Object(_) => Value::Json(json.clone()),
+    }
+}
+
+/// Name of the JSON type.
+pub const fn type_name(json: &Json) -> &'static str {
+    match json {
+        Json::Null => "null",
+        Json::Bool(_) => "bool",
+        Json::Number(_) => "number",
+        Json::String(_) => "string",
+        Json::Array(_) => "array",
+        Json::Object(_) => "object",
+    }
+}
+
+/// Shows the document as a tree of collapsible arrays and objects.
+pub fn show_tree(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, json: &Json) {
+    show_tree_node(ui, egui::Id::new(id_salt), "$", json);
+}
+
+fn show_tree_node(ui: &mut egui::Ui, id: egui::Id, label: &str, json: &Json) {
+    let children: Vec<(String, &Json)> = match *json {
+        Json::Array(ref array) => array
+            .iter()
+            .enumerate()
+            .map(|(idx, json)| (format!("[{idx}]"), json))
+            .collect(),
+        Json::Object(ref object) => object
+            .iter()
+            .map(|(key, json)| (key.clone(), json))
+            .collect(),
+        _ => {
+            ui.label(format!("{label}: {json}"));
+            return;
+        }
+    };
+
+    egui::CollapsingHeader::new(format!(
+        "{label}: {} of {}",
+        type_name(json),
+        children.len()
+    ))
+    .id_salt(id)
+    .show(ui, |ui| {
+        for (key, child) in children.iter().take(PREVIEW_CHILDREN) {
+            show_tree_node(ui, id.with(key), key, child);
+        }
+        if children.len() > PREVIEW_CHILDREN {
+            ui.weak(format!("{} more", children.len() - PREVIEW_CHILDREN));
+        }
+    });
+}
@@ -0,0 +1,524 @@
+//! Node kinds defined by crates that embed the app, and the ports they are described with.
+//!
+//! A kind implements [`NodeKind`] and is [`register`]ed before the app is created.
+//! It is then offered in the graph menu, written in graph text under its tag
+//! and saved with the graph, while the wires and editors of its inputs work as for built-in nodes.
+//!
+//! Built-in nodes stay variants of [`super::Nodes`]: editing them may rewire other nodes
+//! of the graph, while a kind only sees its own inputs.
+
+use std::cell::RefCell;
+use std::sync::{PoisonError, RwLock};
+
+use super::{PinCompat, Value, PIN_JSON, PIN_STR};
+
+/// Type of an input or output.
+#[derive(Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum PortType {
+    Number,
+    String,
+    Bool,
+    Vec2,
+    Vec3,
+    Vec4,
+    Color,
+    List,
+    Json,
+}
+
+impl PortType {
+    pub const ALL: [PortType; 9] = [
+        PortType::Number,
+        PortType::String,
+        PortType::Bool,
+        PortType::Vec2,
+        PortType::Vec3,
+        PortType::Vec4,
+        PortType::Color,
+        PortType::List,
+        PortType::Json,
+    ];
+
+    /// Name of the type in script headers and errors.
+    pub const fn name(self) -> &'static str {
+        match self {
+            PortType::Number => "number",
+            PortType::String => "string",
+            PortType::Bool => "bool",
+            PortType::Vec2 => "vec2",
+            PortType::Vec3 => "vec3",
+            PortType::Vec4 => "vec4",
+            PortType::Color => "color",
+            PortType::List => "list",
+            PortType::Json => "json",
+        }
+    }
+
+    /// Value of unwired inputs, and of outputs while the script fails.
+    pub fn initial(self) -> Value {
+        match self {
+            PortType::Number => Value::Number(0.0),
+            PortType::String => Value::String(String::new()),
+            PortType::Bool => Value::Bool(false),
+            PortType::Vec2 => Value::Vector(vec![0.0; 2]),
+            PortType::Vec3 => Value::Vector(vec![0.0; 3]),
+            PortType::Vec4 => Value::Vector(vec![0.0; 4]),
+            PortType::Color => Value::Color([0.0, 0.0, 0.0, 1.0]),
+            PortType::List => Value::List(Vec::new()),
+            PortType::Json => Value::Json(serde_json::Value::Null),
+        }
+    }
+
+    /// JSON inputs also take JSON text.
+    pub(super) fn in_compat(self) -> PinCompat {
+        match self {
+            PortType::Json => PIN_JSON | PIN_STR,
+            ty => ty.out_compat(),
+        }
+    }
+
+    pub(super) fn out_compat(self) -> PinCompat {
+        self.initial().compat()
+    }
+}
+
+/// Named input or output of a script or custom node.
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Port {
+    pub name: String,
+    pub ty: PortType,
+}
+
+impl Port {
+    pub fn new(name: impl Into<String>, ty: PortType) -> Self {
+        Port {
+            name: name.into(),
+            ty,
+        }
+    }
+
+    /// Name with type, wires follow ports across edits only if both stay the same.
+    pub(super) fn key(&self) -> String {
+        format!("{}: {}", self.name, self.ty.name())
+    }
+}
+
+/// Node type added by a crate that embeds the app.
+///
+/// Inputs are edited in the node while unwired, and outputs are computed from their values.
+///
+/// ```
+/// use nodes::{register, NodeKind, Port, PortType, Value};
+///
+/// #[derive(Clone)]
+/// struct Repeat;
+///
+/// impl NodeKind for Repeat {
+///     fn tag(&self) -> &str {
+///         "Repeat"
+///     }
+///
+///     fn title(&self) -> String {
+///         "Repeat".to_owned()
+///     }
+///
+///     fn description(&self) -> String {
+///         "Repeats text the given number of times".to_owned()
+///     }
+///
+///     fn inputs(&self) -> Vec<Port> {
+///         vec![Port::new("Text", PortType::String), Port::new("Count", PortType::Number)]
+///     }
+///
+///     fn outputs(&self) -> Vec<Port> {
+///         vec![Port::new("Repeated", PortType::String)]
+///     }
+///
+///     fn eval(&self, inputs: &[Value]) -> Result<Vec<Value>, String> {
+///         let count = inputs[1].number();
+///         if !(0.0..=1000.0).contains(&count) {
+///             return Err(format!("Can't repeat {count} times"));
+///         }
+///         let text = inputs[0].string().repeat(count as usize);
+///         Ok(vec![Value::String(text)])
+///     }
+///
+///     fn clone_kind(&self) -> Box<dyn NodeKind> {
+///         Box::new(self.clone())
+///     }
+/// }
+///
+/// register(|| Box::new(Repeat));
+///
+/// let inputs = [Value::String("ab".to_owned()), Value::Number(3.0)];
+/// let outputs = Repeat.eval(&inputs).unwrap();
+/// assert_eq!(outputs[0].string(), "ababab");
+/// assert!(Repeat.eval(&[Value::Bool(true), Value::Number(-1.0)]).is_err());
+/// ```
+pub trait NodeKind {
+    /// Name the node is saved and written in graph text under, an identifier unique among kinds.
+    fn tag(&self) -> &str;
+
+    /// Title shown in the node header and the graph menu.
+    fn title(&self) -> String;
+
+    /// Shown while hovering the node.
+    fn description(&self) -> String;
+
+    fn inputs(&self) -> Vec<Port>;
+
+    fn outputs(&self) -> Vec<Port>;
+
+    /// Values of the outputs, in order, from the values of the inputs.
+    /// The error is shown under the node.
+    fn eval(&self, inputs: &[Value]) -> Result<Vec<Value>, String>;
+
+    /// Whether [`NodeKind::show_body`] has anything to show.
+    fn has_body(&self) -> bool {
+        false
+    }
+
+    /// Shows controls of the node state between its inputs and outputs.
+    /// Returns whether the state changed, after which the ports are read again.
+    fn show_body(&mut self, _ui: &mut egui::Ui) -> bool {
+        false
+    }
+
+    /// State saved with the graph besides the input values.
+    fn save(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    /// Restores state returned by [`NodeKind::save`].
+    fn load(&mut self, _state: serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn clone_kind(&self) -> Box<dyn NodeKind>;
+}
+
+impl Clone for Box<dyn NodeKind> {
+    fn clone(&self) -> Self {
+        self.clone_kind()
+    }
+}
+
+/// Creates node of a registered kind.
+type Constructor = fn() -> Box<dyn NodeKind>;
+
+/// Registered kind with the tag and title of its nodes, read once when it is registered.
+struct Entry {
+    tag: String,
+    title: String,
+    new: Constructor,
+}
+
+static REGISTRY: RwLock<Vec<Entry>> = RwLock::new(Vec::new());
+
+/// Adds a kind to the graph menu, replacing the one with the same tag.
+///
+/// Kinds are registered before the app is created, so that saved graphs find them.
+pub fn register(new: Constructor) {
+    let kind = new();
+    let entry = Entry {
+        tag: kind.tag().to_owned(),
+        title: kind.title(),
+        new,
+    };
+    let mut registry = REGISTRY.write().unwrap_or_else(PoisonError::into_inner);
+    registry.retain(|other| other.tag != entry.tag);
+    registry.push(entry);
+}
+
+/// Tags and titles of the registered kinds, in the order they were registered.
+pub(super) fn registered() -> Vec<(String, String)> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    registry
+        .iter()
+        .map(|entry| (entry.tag.clone(), entry.title.clone()))
+        .collect()
+}
+
+/// Whether a kind is registered with `tag`.
+pub(super) fn is_registered(tag: &str) -> bool {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    registry.iter().any(|entry| entry.tag == tag)
+}
+
+/// New node of the kind registered with `tag`.
+pub(super) fn create(tag: &str) -> Option<Box<dyn NodeKind>> {
+    let registry = REGISTRY.read().unwrap_or_else(PoisonError::into_inner);
+    let new = registry.iter().find(|entry| entry.tag == tag)?.new;
+    // Constructors may register kinds themselves, so the lock is released first.
+    drop(registry);
+    Some(new())
+}
+
+/// Stands in for a kind that isn't registered, keeping its state to save it back.
+#[derive(Clone)]
+struct Missing {
+    tag: String,
+    state: serde_json::Value,
+}
+
+impl NodeKind for Missing {
+    fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    fn title(&self) -> String {
+        self.tag.clone()
+    }
+
+    fn description(&self) -> String {
+        format!("Node of the kind `{}`, which isn't registered", self.tag)
+    }
+
+    fn inputs(&self) -> Vec<Port> {
+        Vec::new()
+    }
+
+    fn outputs(&self) -> Vec<Port> {
+        Vec::new()
+    }
+
+    fn eval(&self, _inputs: &[Value]) -> Result<Vec<Value>, String> {
+        Err(format!("Node kind `{}` isn't registered", self.tag))
+    }
+
+    fn save(&self) -> serde_json::Value {
+        self.state.clone()
+    }
+
+    fn clone_kind(&self) -> Box<dyn NodeKind> {
+        Box::new(self.clone())
+    }
+}
+
+/// Node as saved, with the ports so that wires survive while its kind isn't registered.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Saved {
+    tag: String,
+    state: serde_json::Value,
+    ins: Vec<Port>,
+    outs: Vec<Port>,
+    inputs: Vec<Value>,
+}
+
+/// Inputs of the last evaluation with its result, which is reused while they stay the same.
+type Cached = (Vec<Value>, Result<Vec<Value>, String>);
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(from = "Saved", into = "Saved")]
+pub struct CustomNode {
+    pub(super) kind: Box<dyn NodeKind>,
+    /// Ports read from the kind, kept from the saved graph while it isn't registered.
+    pub(super) ins: Vec<Port>,
+    pub(super) outs: Vec<Port>,
+    /// Values passed to [`NodeKind::eval`], one per port of the kind, kept while unwired.
+    pub(super) inputs: Vec<Value>,
+    cache: RefCell<Option<Cached>>,
+}
+
+impl CustomNode {
+    pub fn new(kind: Box<dyn NodeKind>) -> Self {
+        let mut node = CustomNode {
+            kind,
+            ins: Vec::new(),
+            outs: Vec::new(),
+            inputs: Vec::new(),
+            cache: RefCell::new(None),
+        };
+        node.refresh();
+        node
+    }
+
+    /// Reads the ports of the kind again.
+    /// Inputs keep their values while their name and type stay the same.
+    fn refresh(&mut self) {
+        let ins = self.kind.inputs();
+        self.inputs = ins
+            .iter()
+            .map(|port| {
+                self.ins
+                    .iter()
+                    .position(|old| old == port)
+                    .and_then(|idx| self.inputs.get(idx).cloned())
+                    .unwrap_or_else(|| port.ty.initial())
+            })
+            .collect();
+        self.ins = ins;
+        self.outs = self.kind.outputs();
+        *self.cache.get_mut() = None;
+    }
+
+    /// Keys of the inputs for [`super::rebind_inputs`].
+    pub(super) fn in_keys(&self) -> Vec<String> {
+        self.ins.iter().map(Port::key).collect()
+    }
+
+    /// Keys of the outputs for [`super::rebind_outputs`].
+    pub(super) fn out_keys(&self) -> Vec<String> {
+        self.outs.iter().map(Port::key).collect()
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        &self.ins[idx].name
+    }
+
+    pub(super) fn label_out(&self, idx: usize) -> &str {
+        &self.outs[idx].name
+    }
+
+    /// None for nodes without inputs, which are still offered for dropped wires.
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        self.ins.get(idx).map_or(0, |port| port.ty.in_compat())
+    }
+
+    /// None for nodes without outputs, which are still drawn with the color of the first.
+    pub(super) fn out_compat(&self, idx: usize) -> PinCompat {
+        self.outs.get(idx).map_or(0, |port| port.ty.out_compat())
+    }
+
+    /// Shows the body of the kind, returns whether its ports may have changed.
+    pub(super) fn show_body(&mut self, ui: &mut egui::Ui) -> bool {
+        let changed = self.kind.show_body(ui);
+        if changed {
+            self.refresh();
+        }
+        changed
+    }
+
+    pub(super) fn error(&self) -> Option<String> {
+        self.eval().err()
+    }
+
+    pub(super) fn value_out(&self, idx: usize) -> Value {
+        self.eval()
+            .ok()
+            .and_then(|outputs| outputs.get(idx).cloned())
+            .unwrap_or_else(|| self.outs[idx].ty.initial())
+    }
+
+    /// Outputs for the current inputs, evaluating the kind if they changed.
+    fn eval(&self) -> Result<Vec<Value>, String> {
+        if let Some((ref inputs, ref result)) = *self.cache.borrow() {
+            if *inputs == self.inputs {
+                return result.clone();
+            }
+        }
+
+        let result = self.kind.eval(&self.inputs).and_then(|outputs| {
+            if outputs.len() == self.outs.len() {
+                Ok(outputs)
+            } else {
+                Err(format!(
+                    "Expected {} outputs, got {}",
+                    self.outs.len(),
+                    outputs.len()
+                ))
+            }
+        });
+        *self.cache.borrow_mut() = Some((self.inputs.clone(), result.clone()));
+        result
+    }
+}
+
+impl From<Saved> for CustomNode {
+    fn from(saved: Saved) -> Self {
+        let Some(mut kind) = create(&saved.tag) else {
+            return CustomNode {
+                kind: Box::new(Missing {
+                    tag: saved.tag,
+                    state: saved.state,
+                }),
+                ins: saved.ins,
+                outs: saved.outs,
+                inputs: saved.inputs,
+                cache: RefCell::new(None),
+            };
+        };
+
+        if let Err(err) = kind.load(saved.state) {
+            log::warn!("Failed to load `{}` node: {err}", saved.tag);
+        }
+        let mut node = CustomNode {
+            kind,
+            ins: saved.ins,
+            outs: saved.outs,
+            inputs: saved.inputs,
+            cache: RefCell::new(None),
+        };
+        node.refresh();
+        node
+    }
+}
+
+impl From<CustomNode> for Saved {
+    fn from(node: CustomNode) -> Self {
+        Saved {
+            tag: node.kind.tag().to_owned(),
+            state: node.kind.save(),
+            ins: node.ins,
+            outs: node.outs,
+            inputs: node.inputs,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Scale(f64);
+
+    impl NodeKind for Scale {
+        fn tag(&self) -> &str {
+            "TestScale"
+        }
+
+        fn title(&self) -> String {
+            format!("Scale by {}", self.0)
+        }
+
+        fn description(&self) -> String {
+            String::new()
+        }
+
+        fn inputs(&self) -> Vec<Port> {
+            vec![Port::new("x", PortType::Number)]
+        }
+
+        fn outputs(&self) -> Vec<Port> {
+            vec![Port::new("y", PortType::Number)]
+        }
+
+        fn eval(&self, inputs: &[Value]) -> Result<Vec<Value>, String> {
+            Ok(vec![Value::Number(inputs[0].number() * self.0)])
+        }
+
+        fn clone_kind(&self) -> Box<dyn NodeKind> {
+            Box::new(self.clone())
+        }
+    }
+
+    #[test]
+    fn registers_kinds_by_tag() {
+        register(|| Box::new(Scale(2.0)));
+        register(|| Box::new(Scale(3.0)));
+        let titles = registered()
+            .into_iter()
+            .filter(|(tag, _)| tag == "TestScale")
+            .map(|(_, title)| title)
+            .collect::<Vec<_>>();
+        assert_eq!(titles, ["Scale by 3"]);
+        assert!(is_registered("TestScale"));
+        assert!(create("TestMissing").is_none());
+
+        let mut node = CustomNode::new(create("TestScale").expect("kind is registered"));
+        node.inputs[0] = Value::Number(1.5);
+        assert!(node.error().is_none());
+        assert_eq!(node.value_out(0).number(), 4.5);
+    }
+}
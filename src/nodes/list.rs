@@ -0,0 +1,287 @@
+//! List nodes.
+
+use super::{Expr, PinCompat, Value};
+
+/// Most elements a Range node outputs.
+const MAX_RANGE_LEN: usize = 100_000;
+
+node_ops! {
+    pub enum ListOp {
+        Range: "Range", "Outputs numbers from Start up to End, excluding it";
+        /// Evaluates the expression for each element, bound to `x` with its index `idx`.
+        Map: "Map", "Evaluates the expression for each element `x` at index `idx`";
+        /// Keeps elements for which the expression is not zero.
+        Filter: "Filter", "Keeps elements `x` at index `idx` for which the expression is not zero";
+        /// Folds elements into `acc`, starting from the initial value.
+        Reduce: "Reduce", "Folds elements `x` at index `idx` into `acc` with the expression";
+        Sum: "Sum", "Outputs sum of the elements";
+        Min: "Min", "Outputs the smallest element";
+        Max: "Max", "Outputs the largest element";
+        Length as "ListLength": "List Length", "Outputs number of elements";
+        Index: "Index", "Outputs element at the index, negative ones count from the end";
+        Sort: "Sort", "Sorts numbers ascending and strings alphabetically";
+        Zip: "Zip", "Pairs elements of two lists into Vec2s";
+    }
+}
+
+impl ListOp {
+    /// Bindings the expression of Map, Filter and Reduce may use.
+    const fn bindings(self) -> &'static [&'static str] {
+        match self {
+            ListOp::Reduce => &["acc", "x", "idx"],
+            _ => &["x", "idx"],
+        }
+    }
+
+    /// Labels and initial values of the inputs.
+    fn inputs(self) -> Vec<(&'static str, Value)> {
+        let list = || ("List", Value::List(Vec::new()));
+        match self {
+            ListOp::Range => vec![
+                ("Start", Value::Number(0.0)),
+                ("End", Value::Number(10.0)),
+                ("Step", Value::Number(1.0)),
+            ],
+            ListOp::Map | ListOp::Filter => {
+                vec![list(), ("Expr", Value::String("x".to_owned()))]
+            }
+            ListOp::Reduce => vec![
+                list(),
+                ("Expr", Value::String("acc + x".to_owned())),
+                ("Initial", Value::Number(0.0)),
+            ],
+            ListOp::Sum | ListOp::Min | ListOp::Max | ListOp::Length | ListOp::Sort => {
+                vec![list()]
+            }
+            ListOp::Index => vec![list(), ("Index", Value::Number(0.0))],
+            ListOp::Zip => vec![
+                ("A", Value::List(Vec::new())),
+                ("B", Value::List(Vec::new())),
+            ],
+        }
+    }
+}
+
+/// Node as saved, read through [`ListNode::from`] to update expressions of older graphs.
+#[derive(serde::Deserialize)]
+struct Saved {
+    op: ListOp,
+    inputs: Vec<Value>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(from = "Saved")]
+pub struct ListNode {
+    pub(super) op: ListOp,
+    /// List operated on and the arguments of the operation, such as Map's expression or the index.
+    pub(super) inputs: Vec<Value>,
+}
+
+impl ListNode {
+    pub fn new(op: ListOp) -> Self {
+        ListNode {
+            op,
+            inputs: op.inputs().into_iter().map(|(_, value)| value).collect(),
+        }
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        self.op.inputs()[idx].0
+    }
+
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        self.inputs[idx].compat()
+    }
+
+    /// Index outputs the type of the element.
+    pub(super) fn out_compat(&self) -> PinCompat {
+        self.value_out().compat()
+    }
+
+    /// Parsed expression of Map, Filter and Reduce with its bindings.
+    pub(super) fn expr(&self) -> Result<(Expr, Vec<String>), String> {
+        let text = self.inputs[1].string();
+        let expr = syn::parse_str::<Expr>(&text).map_err(|err| err.to_string())?;
+        if expr.is_complex() {
+            return Err("`i` is the imaginary unit, the index is bound to `idx`".to_owned());
+        }
+        let mut bindings = Vec::new();
+        expr.extend_bindings(&mut bindings);
+
+        let allowed = self.op.bindings();
+        if let Some(unknown) = bindings
+            .iter()
+            .find(|binding| !allowed.contains(&binding.as_str()))
+        {
+            return Err(format!(
+                "Unknown binding `{unknown}`, expected {}",
+                allowed.join(", ")
+            ));
+        }
+        Ok((expr, bindings))
+    }
+
+    /// Renames the index binding in expressions saved while it was `i`, now the imaginary unit.
+    fn migrate_expr(text: &str) -> String {
+        let mut migrated = String::with_capacity(text.len());
+        let mut word = String::new();
+        for c in text.chars().chain([' ']) {
+            if c.is_alphanumeric() || c == '_' {
+                word.push(c);
+                continue;
+            }
+            migrated.push_str(if word == "i" { "idx" } else { &word });
+            word.clear();
+            migrated.push(c);
+        }
+        migrated.pop();
+        migrated
+    }
+
+    fn list_in(&self, idx: usize) -> &[Value] {
+        match self.inputs[idx] {
+            Value::List(ref items) => items,
+            _ => &[],
+        }
+    }
+
+    pub(super) fn value_out(&self) -> Value {
+        let numbers = || self.list_in(0).iter().map(Value::number);
+
+        match self.op {
+            ListOp::Range => {
+                let [start, end, step] = [0, 1, 2].map(|idx| self.inputs[idx].number());
+                let len = ((end - start) / step).ceil();
+                let len = if len.is_finite() && len > 0.0 {
+                    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                    let len = len as usize;
+                    len.min(MAX_RANGE_LEN)
+                } else {
+                    0
+                };
+                Value::List(
+                    (0..len)
+                        .map(|idx| Value::Number(start + idx as f64 * step))
+                        .collect(),
+                )
+            }
+            ListOp::Map => {
+                let Ok((expr, bindings)) = self.expr() else {
+                    return Value::List(Vec::new());
+                };
+                Value::List(
+                    numbers()
+                        .enumerate()
+                        .map(|(idx, x)| Value::Number(eval(&expr, &bindings, 0.0, x, idx)))
+                        .collect(),
+                )
+            }
+            ListOp::Filter => {
+                let Ok((expr, bindings)) = self.expr() else {
+                    return Value::List(Vec::new());
+                };
+                Value::List(
+                    self.list_in(0)
+                        .iter()
+                        .enumerate()
+                        .filter(|&(idx, item)| {
+                            eval(&expr, &bindings, 0.0, item.number(), idx) != 0.0
+                        })
+                        .map(|(_, item)| item.clone())
+                        .collect(),
+                )
+            }
+            ListOp::Reduce => {
+                let initial = self.inputs[2].number();
+                let Ok((expr, bindings)) = self.expr() else {
+                    return Value::Number(initial);
+                };
+                Value::Number(
+                    numbers()
+                        .enumerate()
+                        .fold(initial, |acc, (idx, x)| eval(&expr, &bindings, acc, x, idx)),
+                )
+            }
+            ListOp::Sum => Value::Number(numbers().sum()),
+            ListOp::Min => Value::Number(numbers().reduce(f64::min).unwrap_or(f64::NAN)),
+            ListOp::Max => Value::Number(numbers().reduce(f64::max).unwrap_or(f64::NAN)),
+            ListOp::Length => Value::Number(self.list_in(0).len() as f64),
+            ListOp::Index => {
+                let items = self.list_in(0);
+                let idx = self.inputs[1].number().floor();
+                let idx = if idx < 0.0 {
+                    idx + items.len() as f64
+                } else {
+                    idx
+                };
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let item = (idx >= 0.0).then(|| items.get(idx as usize)).flatten();
+                item.cloned().unwrap_or(Value::Number(f64::NAN))
+            }
+            ListOp::Sort => {
+                let mut items = self.list_in(0).to_vec();
+                items.sort_by(|a, b| match (a, b) {
+                    (Value::String(a), Value::String(b)) => a.cmp(b),
+                    _ => a.number().total_cmp(&b.number()),
+                });
+                Value::List(items)
+            }
+            ListOp::Zip => Value::List(
+                self.list_in(0)
+                    .iter()
+                    .zip(self.list_in(1))
+                    .map(|(a, b)| Value::Vector(vec![a.number(), b.number()]))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Evaluates expression of Map, Filter or Reduce for one element.
+fn eval(expr: &Expr, bindings: &[String], acc: f64, x: f64, idx: usize) -> f64 {
+    let args = bindings
+        .iter()
+        .map(|binding| match binding.as_str() {
+            "acc" => acc,
+            "x" => x,
+            _ => idx as f64,
+        })
+        .collect::<Vec<_>>();
+    expr.eval(bindings, &args)
+}
+
+/// Shows elements of the list in a scrollable table with their indices.
+pub fn show_table(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    items: &[Value],
+    precision: u8,
+) {
+    egui::ScrollArea::vertical()
+        .id_salt(&id_salt)
+        .max_height(200.0)
+        .show(ui, |ui| {
+            egui::Grid::new(id_salt).striped(true).show(ui, |ui| {
+                for (idx, item) in items.iter().enumerate() {
+                    ui.label(idx.to_string());
+                    super::show_value(ui, item, precision);
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+impl From<Saved> for ListNode {
+    fn from(saved: Saved) -> Self {
+        let mut inputs = saved.inputs;
+        if matches!(saved.op, ListOp::Map | ListOp::Filter | ListOp::Reduce) {
+            if let Some(Value::String(text)) = inputs.get_mut(1) {
+                *text = ListNode::migrate_expr(text);
+            }
+        }
+        ListNode {
+            op: saved.op,
+            inputs,
+        }
+    }
+}
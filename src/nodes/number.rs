@@ -0,0 +1,64 @@
+//! Number source with an optional unit.
+
+use super::units::{self, Quantity};
+
+/// Number typed or dragged in, with the unit it is given in.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(from = "Saved")]
+pub struct NumberNode {
+    pub(super) value: f64,
+    /// Unit shown after the value, such as `m` or `%`.
+    pub(super) unit: String,
+}
+
+/// Node as saved, either with its unit or as a bare number by graphs from before units.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Saved {
+    Plain(f64),
+    WithUnit {
+        value: f64,
+        #[serde(default)]
+        unit: String,
+    },
+}
+
+impl NumberNode {
+    pub fn new(value: f64) -> Self {
+        NumberNode {
+            value,
+            unit: String::new(),
+        }
+    }
+
+    /// Value in base units if the unit is known, as a plain number otherwise.
+    pub(super) fn quantity_out(&self) -> Quantity {
+        units::quantity(self.value, &self.unit)
+    }
+
+    /// Shows the value to drag or type, followed by its unit.
+    pub(super) fn show_output(&mut self, ui: &mut egui::Ui) {
+        let mut drag = egui::DragValue::new(&mut self.value);
+        if !self.unit.is_empty() {
+            drag = drag.suffix(format!(" {}", self.unit));
+        }
+        ui.add(drag);
+    }
+
+    /// Shows the unit to edit.
+    pub(super) fn show_body(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Unit");
+            ui.add(egui::TextEdit::singleline(&mut self.unit).desired_width(40.0));
+        });
+    }
+}
+
+impl From<Saved> for NumberNode {
+    fn from(saved: Saved) -> Self {
+        match saved {
+            Saved::Plain(value) => NumberNode::new(value),
+            Saved::WithUnit { value, unit } => NumberNode { value, unit },
+        }
+    }
+}
@@ -0,0 +1,373 @@
+//! Number representations that Expr nodes compute with, and the precision numbers are shown with.
+
+/// Digits after the decimal point of [`Real::Decimal`].
+const PLACES: u32 = 9;
+
+/// `1` as [`Real::Decimal`].
+const ONE: i128 = 10i128.pow(PLACES);
+
+/// Digits shown after the decimal point unless the graph sets its own.
+pub const DEFAULT_PRECISION: u8 = 3;
+
+/// Representation Expr nodes evaluate with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Backend {
+    /// Binary floating point, fast but inexact for most decimal fractions.
+    #[default]
+    Float,
+    /// Fixed point with nine digits after the decimal point.
+    Decimal,
+    /// Exact fractions of integers.
+    Rational,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::Float, Backend::Decimal, Backend::Rational];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Backend::Float => "Float",
+            Backend::Decimal => "Decimal",
+            Backend::Rational => "Rational",
+        }
+    }
+
+    pub const fn description(self) -> &'static str {
+        match self {
+            Backend::Float => "64-bit floating point, as in most calculators",
+            Backend::Decimal => "Fixed point with 9 decimals, exact for money",
+            Backend::Rational => "Exact fractions, such as 1/3",
+        }
+    }
+}
+
+/// Numeric settings of a graph.
+#[derive(Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Numeric {
+    pub backend: Backend,
+    /// Digits shown after the decimal point.
+    pub precision: u8,
+}
+
+impl Default for Numeric {
+    fn default() -> Self {
+        Numeric {
+            backend: Backend::Float,
+            precision: DEFAULT_PRECISION,
+        }
+    }
+}
+
+impl Numeric {
+    /// Shows the backend and precision choices.
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        for backend in Backend::ALL {
+            ui.radio_value(&mut self.backend, backend, backend.name())
+                .on_hover_text(backend.description());
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Decimals shown");
+            ui.add(egui::DragValue::new(&mut self.precision).range(0..=15));
+        });
+    }
+}
+
+/// Number in one of the [`Backend`] representations.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Real {
+    Float(f64),
+    /// Value times 10⁹.
+    Decimal(i128),
+    /// Numerator and positive denominator, without common factors.
+    Rational(i128, i128),
+}
+
+impl Real {
+    /// Number in `backend`, reading floats by their shortest decimal form so that `0.1` is exact.
+    pub fn from_f64(value: f64, backend: Backend) -> Result<Real, String> {
+        if backend == Backend::Float {
+            return Ok(Real::Float(value));
+        }
+        if !value.is_finite() {
+            return Err(format!("{value} has no exact value"));
+        }
+
+        // Floats are written without exponent, with the shortest digits that read back the same.
+        let text = value.to_string();
+        let (negative, text) = match text.strip_prefix('-') {
+            Some(text) => (true, text),
+            None => (false, &*text),
+        };
+        let (int, frac) = text.split_once('.').unwrap_or((text, ""));
+        let mut digits = format!("{int}{frac}")
+            .parse::<i128>()
+            .map_err(|_| overflow(backend))?;
+        if negative {
+            digits = -digits;
+        }
+
+        let exponent = u32::try_from(frac.len()).map_err(|_| overflow(backend))?;
+        let scale = 10i128
+            .checked_pow(exponent)
+            .ok_or_else(|| overflow(backend))?;
+        Real::Rational(digits, 1)
+            .div(Real::Rational(scale, 1))?
+            .convert(backend)
+    }
+
+    pub const fn zero(backend: Backend) -> Real {
+        match backend {
+            Backend::Float => Real::Float(0.0),
+            Backend::Decimal => Real::Decimal(0),
+            Backend::Rational => Real::Rational(0, 1),
+        }
+    }
+
+    pub fn abs(self) -> Result<Real, String> {
+        if self.to_f64().is_sign_negative() {
+            self.neg()
+        } else {
+            Ok(self)
+        }
+    }
+
+    pub const fn backend(self) -> Backend {
+        match self {
+            Real::Float(_) => Backend::Float,
+            Real::Decimal(_) => Backend::Decimal,
+            Real::Rational(..) => Backend::Rational,
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    pub fn to_f64(self) -> f64 {
+        match self {
+            Real::Float(value) => value,
+            Real::Decimal(raw) => raw as f64 / ONE as f64,
+            Real::Rational(num, den) => num as f64 / den as f64,
+        }
+    }
+
+    /// Same number in `backend`, rounded if it has no exact representation there.
+    pub fn convert(self, backend: Backend) -> Result<Real, String> {
+        match (self, backend) {
+            (_, Backend::Float) => Ok(Real::Float(self.to_f64())),
+            (Real::Float(value), _) => Real::from_f64(value, backend),
+            (Real::Decimal(_), Backend::Decimal) | (Real::Rational(..), Backend::Rational) => {
+                Ok(self)
+            }
+            (Real::Decimal(raw), Backend::Rational) => rational(raw, ONE),
+            (Real::Rational(num, den), Backend::Decimal) => num
+                .checked_mul(ONE)
+                .and_then(|num| div_round(num, den))
+                .map(Real::Decimal)
+                .ok_or_else(|| overflow(backend)),
+        }
+    }
+
+    pub fn neg(self) -> Result<Real, String> {
+        match self {
+            Real::Float(value) => Ok(Real::Float(-value)),
+            Real::Decimal(raw) => raw
+                .checked_neg()
+                .map(Real::Decimal)
+                .ok_or_else(|| overflow(Backend::Decimal)),
+            Real::Rational(num, den) => num
+                .checked_neg()
+                .map(|num| Real::Rational(num, den))
+                .ok_or_else(|| overflow(Backend::Rational)),
+        }
+    }
+
+    pub fn add(self, other: Real) -> Result<Real, String> {
+        let backend = self.backend();
+        match (self, other.convert(backend)?) {
+            (Real::Float(lhs), Real::Float(rhs)) => Ok(Real::Float(lhs + rhs)),
+            (Real::Decimal(lhs), Real::Decimal(rhs)) => lhs
+                .checked_add(rhs)
+                .map(Real::Decimal)
+                .ok_or_else(|| overflow(backend)),
+            (Real::Rational(a, b), Real::Rational(c, d)) => {
+                let num = a
+                    .checked_mul(d)
+                    .zip(c.checked_mul(b))
+                    .and_then(|(ad, cb)| ad.checked_add(cb));
+                let den = b.checked_mul(d);
+                rational(
+                    num.ok_or_else(|| overflow(backend))?,
+                    den.ok_or_else(|| overflow(backend))?,
+                )
+            }
+            _ => unreachable!("Operands are converted to the same backend"),
+        }
+    }
+
+    pub fn mul(self, other: Real) -> Result<Real, String> {
+        let backend = self.backend();
+        match (self, other.convert(backend)?) {
+            (Real::Float(lhs), Real::Float(rhs)) => Ok(Real::Float(lhs * rhs)),
+            (Real::Decimal(lhs), Real::Decimal(rhs)) => lhs
+                .checked_mul(rhs)
+                .and_then(|raw| div_round(raw, ONE))
+                .map(Real::Decimal)
+                .ok_or_else(|| overflow(backend)),
+            (Real::Rational(a, b), Real::Rational(c, d)) => {
+                // Cancelling first keeps the products small.
+                let (ad, cb) = gcd(a, d).zip(gcd(c, b)).ok_or_else(|| overflow(backend))?;
+                let (a, d) = (a / ad, d / ad);
+                let (c, b) = (c / cb, b / cb);
+                a.checked_mul(c)
+                    .zip(b.checked_mul(d))
+                    .ok_or_else(|| overflow(backend))
+                    .and_then(|(num, den)| rational(num, den))
+            }
+            _ => unreachable!("Operands are converted to the same backend"),
+        }
+    }
+
+    /// Divides, exact backends refuse to divide by zero.
+    pub fn div(self, other: Real) -> Result<Real, String> {
+        let backend = self.backend();
+        match (self, other.convert(backend)?) {
+            (Real::Float(lhs), Real::Float(rhs)) => Ok(Real::Float(lhs / rhs)),
+            (_, Real::Decimal(0) | Real::Rational(0, _)) => Err("Division by zero".to_owned()),
+            (Real::Decimal(lhs), Real::Decimal(rhs)) => lhs
+                .checked_mul(ONE)
+                .and_then(|raw| div_round(raw, rhs))
+                .map(Real::Decimal)
+                .ok_or_else(|| overflow(backend)),
+            (Real::Rational(..), Real::Rational(num, den)) => {
+                let inverse = if num < 0 {
+                    den.checked_neg()
+                        .zip(num.checked_neg())
+                        .map(|(num, den)| Real::Rational(num, den))
+                        .ok_or_else(|| overflow(backend))?
+                } else {
+                    Real::Rational(den, num)
+                };
+                self.mul(inverse)
+            }
+            _ => unreachable!("Operands are converted to the same backend"),
+        }
+    }
+}
+
+impl std::fmt::Display for Real {
+    /// Writes floats and decimals rounded to the formatter's precision, such as `{:.3}`,
+    /// or else exactly, and fractions exactly.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Real::Float(value) => f.write_str(&super::format_float(value, f.precision())),
+            Real::Decimal(raw) => {
+                let digits = f
+                    .precision()
+                    .and_then(|digits| u32::try_from(digits).ok())
+                    .map_or(PLACES, |digits| digits.min(PLACES));
+                let rounded = div_round(raw, 10i128.pow(PLACES - digits)).ok_or(std::fmt::Error)?;
+                let unit = 10u128.pow(digits);
+                if rounded < 0 {
+                    f.write_str("-")?;
+                }
+                let (int, frac) = (rounded.unsigned_abs() / unit, rounded.unsigned_abs() % unit);
+                write!(f, "{int}")?;
+                if frac != 0 {
+                    let frac = format!("{frac:0width$}", width = digits as usize);
+                    write!(f, ".{}", frac.trim_end_matches('0'))?;
+                }
+                Ok(())
+            }
+            Real::Rational(num, 1) => write!(f, "{num}"),
+            Real::Rational(num, den) => write!(f, "{num}/{den}"),
+        }
+    }
+}
+
+fn overflow(backend: Backend) -> String {
+    format!("Number too large for {}", backend.name().to_lowercase())
+}
+
+/// Greatest common divisor, or `None` if it is 2¹²⁷, which only `i128::MIN` and zero have.
+fn gcd(a: i128, b: i128) -> Option<i128> {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    if a == 0 {
+        Some(1)
+    } else {
+        i128::try_from(a).ok()
+    }
+}
+
+/// Fraction in lowest terms with a positive denominator.
+fn rational(num: i128, den: i128) -> Result<Real, String> {
+    if den == 0 {
+        return Err("Division by zero".to_owned());
+    }
+    let divisor = gcd(num, den).ok_or_else(|| overflow(Backend::Rational))?;
+    let (num, den) = (num / divisor, den / divisor);
+    if den < 0 {
+        num.checked_neg()
+            .zip(den.checked_neg())
+            .map(|(num, den)| Real::Rational(num, den))
+            .ok_or_else(|| overflow(Backend::Rational))
+    } else {
+        Ok(Real::Rational(num, den))
+    }
+}
+
+/// Quotient rounded half away from zero, or `None` if it overflows.
+const fn div_round(num: i128, den: i128) -> Option<i128> {
+    let (Some(quotient), Some(remainder)) = (num.checked_div(den), num.checked_rem(den)) else {
+        return None;
+    };
+    if remainder.unsigned_abs() >= den.unsigned_abs() - remainder.unsigned_abs() {
+        quotient.checked_add(num.signum() * den.signum())
+    } else {
+        Some(quotient)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_overflow_at_the_limits() {
+        assert_eq!(gcd(i128::MIN, 0), None);
+        assert_eq!(gcd(i128::MIN, 6), Some(2));
+        assert_eq!(div_round(i128::MIN, -1), None);
+        assert_eq!(div_round(-7, 2), Some(-4));
+
+        let too_large = Err(overflow(Backend::Rational));
+        assert_eq!(rational(i128::MIN, -1), too_large);
+        assert_eq!(rational(i128::MIN, 0), Err("Division by zero".to_owned()));
+        assert_eq!(
+            Real::Rational(i128::MIN, 1).mul(Real::Rational(-1, 1)),
+            too_large
+        );
+        assert_eq!(
+            Real::Rational(1, 1).div(Real::Rational(i128::MIN, 1)),
+            too_large
+        );
+        assert_eq!(
+            Real::Rational(1, 1).div(Real::Rational(i128::MIN + 1, 1)),
+            Ok(Real::Rational(-1, i128::MAX))
+        );
+        assert_eq!(
+            Real::Decimal(i128::MIN).mul(Real::Decimal(-ONE)),
+            Err(overflow(Backend::Decimal))
+        );
+    }
+
+    #[test]
+    fn formats_with_precision() {
+        assert_eq!(format!("{:.2}", Real::Float(1.0 / 3.0)), "0.33");
+        assert_eq!(format!("{}", Real::Float(1.0 / 3.0)), "0.3333333333333333");
+        assert_eq!(format!("{:.2}", Real::Decimal(-2_345_000_000)), "-2.35");
+        assert_eq!(format!("{}", Real::Decimal(-2_345_000_000)), "-2.345");
+        assert_eq!(format!("{:.2}", Real::Rational(1, 3)), "1/3");
+    }
+}
@@ -0,0 +1,225 @@
+//! Random number nodes with explicit seeds.
+//!
+//! Values are derived from the seed with integer arithmetic, and Gaussian values only add
+//! arithmetic and square roots that IEEE 754 rounds exactly, so a seed gives the same draws
+//! on every platform.
+
+use std::f64::consts::{LN_2, SQRT_2};
+
+use super::{PinCompat, Value};
+
+node_ops! {
+    pub enum RandomOp {
+        Uniform as "Random": "Random", "Outputs a number between Min and Max, drawn uniformly";
+        Gaussian: "Gaussian", "Outputs a number drawn from a normal distribution";
+        Integer as "RandomInt": "Random Int",
+            "Outputs a whole number from Min to Max, both included";
+    }
+}
+
+impl RandomOp {
+    /// Labels and initial values of the inputs.
+    fn inputs(self) -> Vec<(&'static str, Value)> {
+        let range = match self {
+            RandomOp::Uniform => [("Min", 0.0), ("Max", 1.0)],
+            RandomOp::Gaussian => [("Mean", 0.0), ("Std dev", 1.0)],
+            RandomOp::Integer => [("Min", 1.0), ("Max", 6.0)],
+        };
+        range
+            .into_iter()
+            .map(|(label, value)| (label, Value::Number(value)))
+            .chain([
+                ("Seed", Value::Number(0.0)),
+                ("Reseed each evaluation", Value::Bool(false)),
+            ])
+            .collect()
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct RandomNode {
+    pub(super) op: RandomOp,
+    /// Range of the distribution, then the seed and the reseed toggle.
+    pub(super) inputs: Vec<Value>,
+    /// Number of the current evaluation of the graph, mixed into the seed when reseeding.
+    #[serde(skip)]
+    pub(super) evaluation: u64,
+}
+
+impl RandomNode {
+    pub fn new(op: RandomOp) -> Self {
+        RandomNode {
+            op,
+            inputs: op.inputs().into_iter().map(|(_, value)| value).collect(),
+            evaluation: 0,
+        }
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        self.op.inputs()[idx].0
+    }
+
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        self.inputs[idx].compat()
+    }
+
+    /// Draws from the seed, and from the evaluation when reseeding each evaluation.
+    pub(super) fn value_out(&self) -> Value {
+        let number_in = |idx: usize| self.inputs[idx].number();
+
+        let mut state = mix(number_in(2).to_bits());
+        if self.inputs[3].bool() {
+            state = mix(state ^ self.evaluation);
+        }
+        let mut next = || {
+            state = state.wrapping_add(GOLDEN_GAMMA);
+            mix(state)
+        };
+
+        let value = match self.op {
+            RandomOp::Uniform => number_in(0) + (number_in(1) - number_in(0)) * unit(next()),
+            RandomOp::Gaussian => {
+                // Marsaglia's polar method, drawing points until one falls inside the unit circle.
+                let (x, radius) = loop {
+                    let x = 2.0 * unit(next()) - 1.0;
+                    let y = 2.0 * unit(next()) - 1.0;
+                    let radius = x * x + y * y;
+                    if radius > 0.0 && radius < 1.0 {
+                        break (x, radius);
+                    }
+                };
+                number_in(0) + number_in(1) * x * (-2.0 * ln(radius) / radius).sqrt()
+            }
+            #[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+            RandomOp::Integer => {
+                let (min, max) = (number_in(0).round(), number_in(1).round());
+                let (min, max) = (min.min(max) as i64, min.max(max) as i64);
+                let count = max.abs_diff(min).wrapping_add(1);
+                let offset = if count == 0 {
+                    next()
+                } else {
+                    ((u128::from(next()) * u128::from(count)) >> 64) as u64
+                };
+                min.wrapping_add_unsigned(offset) as f64
+            }
+        };
+        Value::Number(value)
+    }
+}
+
+/// Increment of the SplitMix64 generator.
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// SplitMix64 output function, scrambling all bits of the state.
+const fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Number in 0..1 from the top 53 bits.
+#[allow(clippy::cast_precision_loss)]
+fn unit(bits: u64) -> f64 {
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Natural logarithm of a positive normal number.
+///
+/// Unlike `f64::ln`, whose last bit depends on the platform's math library, this only uses
+/// arithmetic, summing the series `ln(m) = 2 atanh((m - 1) / (m + 1))` for the mantissa.
+#[allow(clippy::cast_possible_wrap)]
+fn ln(x: f64) -> f64 {
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let mut mantissa = f64::from_bits(bits & 0x000f_ffff_ffff_ffff | 0x3ff0_0000_0000_0000);
+    if mantissa > SQRT_2 {
+        mantissa /= 2.0;
+        exponent += 1;
+    }
+
+    // With the mantissa in 1/√2..√2, z² is below 0.03 and 12 terms reach full precision.
+    let z = (mantissa - 1.0) / (mantissa + 1.0);
+    let z2 = z * z;
+    let mut term = z;
+    let mut sum = 0.0;
+    for k in 0..12 {
+        sum += term / f64::from(2 * k + 1);
+        term *= z2;
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let exponent = exponent as f64;
+    2.0 * sum + exponent * LN_2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(op: RandomOp, seed: f64, evaluation: Option<u64>) -> f64 {
+        let mut random_node = RandomNode::new(op);
+        random_node.inputs[2] = Value::Number(seed);
+        if let Some(evaluation) = evaluation {
+            random_node.inputs[3] = Value::Bool(true);
+            random_node.evaluation = evaluation;
+        }
+        random_node.value_out().number()
+    }
+
+    #[test]
+    fn seeds_give_fixed_draws() {
+        // Same values natively and on the web, changing them breaks saved experiments.
+        assert_eq!(draw(RandomOp::Uniform, 0.0, None), 0.883_310_808_213_642_6);
+        assert_eq!(
+            draw(RandomOp::Uniform, 42.0, None),
+            0.366_486_701_828_424_24
+        );
+        assert_eq!(draw(RandomOp::Gaussian, 0.0, None), 0.984_527_912_108_398_4);
+        assert_eq!(draw(RandomOp::Gaussian, 42.0, None), -1.192_631_214_353_623);
+        assert_eq!(draw(RandomOp::Integer, 0.0, None), 6.0);
+        assert_eq!(draw(RandomOp::Integer, 42.0, None), 3.0);
+    }
+
+    #[test]
+    fn reseeds_each_evaluation() {
+        for op in RandomOp::ALL {
+            assert_eq!(draw(op, 42.0, Some(7)), draw(op, 42.0, Some(7)));
+        }
+        assert_eq!(
+            draw(RandomOp::Uniform, 42.0, Some(7)),
+            0.166_749_103_085_944_48
+        );
+        assert_ne!(
+            draw(RandomOp::Uniform, 42.0, Some(7)),
+            draw(RandomOp::Uniform, 42.0, Some(8))
+        );
+
+        let mut random_node = RandomNode::new(RandomOp::Uniform);
+        let first = random_node.value_out();
+        random_node.evaluation = 1;
+        assert!(random_node.value_out() == first);
+    }
+
+    #[test]
+    fn gaussian_is_standard_normal() {
+        let draws = (0..20_000)
+            .map(|seed| draw(RandomOp::Gaussian, f64::from(seed), None))
+            .collect::<Vec<_>>();
+        let mean = draws.iter().sum::<f64>() / 20_000.0;
+        let variance = draws.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / 20_000.0;
+        assert!(mean.abs() < 0.03, "mean {mean}");
+        assert!((variance - 1.0).abs() < 0.05, "variance {variance}");
+    }
+
+    #[test]
+    fn ln_matches_std() {
+        for x in [
+            1e-300, 1e-9, 0.1, 0.5, 0.7, 0.999_999, 1.0, 1.5, 2.0, 10.0, 1e300,
+        ] {
+            let error = (ln(x) - x.ln()).abs();
+            assert!(
+                error <= 4.0 * f64::EPSILON * x.ln().abs().max(1.0),
+                "ln({x})"
+            );
+        }
+    }
+}
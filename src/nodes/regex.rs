@@ -0,0 +1,97 @@
+//! Regex matching node.
+
+use std::cell::OnceCell;
+
+use ::regex::Regex;
+
+/// Output pins before the capture groups.
+pub const FIXED_OUTPUTS: [&str; 2] = ["matched", "match"];
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct RegexNode {
+    pub(super) pattern: String,
+    /// Input string, copied from the remote while connected.
+    pub(super) text: String,
+    /// Output name of each capture group, its index for unnamed groups.
+    pub(super) groups: Vec<String>,
+    #[serde(skip)]
+    regex: OnceCell<Result<Regex, String>>,
+}
+
+impl RegexNode {
+    pub fn new() -> Self {
+        RegexNode {
+            pattern: String::new(),
+            text: String::new(),
+            groups: Vec::new(),
+            regex: OnceCell::new(),
+        }
+    }
+
+    /// Creates node with `pattern`, keeping no groups if it doesn't compile.
+    pub fn with_pattern(pattern: String) -> Self {
+        let mut node = RegexNode::new();
+        node.set_pattern(pattern);
+        node
+    }
+
+    /// Compiled pattern or the compile error.
+    pub(super) fn regex(&self) -> Result<&Regex, &str> {
+        self.regex
+            .get_or_init(|| Regex::new(&self.pattern).map_err(|err| err.to_string()))
+            .as_ref()
+            .map_err(String::as_str)
+    }
+
+    /// Replaces the pattern and updates groups if it compiles.
+    ///
+    /// Groups are kept while the pattern is invalid so that wires survive typing.
+    pub(super) fn set_pattern(&mut self, pattern: String) {
+        self.pattern = pattern;
+        self.regex = OnceCell::new();
+        if let Ok(regex) = self.regex() {
+            let groups = regex
+                .capture_names()
+                .enumerate()
+                .skip(1)
+                .map(|(idx, name)| name.map_or_else(|| idx.to_string(), str::to_owned))
+                .collect();
+            self.groups = groups;
+        }
+    }
+
+    pub(super) fn label_out(&self, idx: usize) -> &str {
+        match idx {
+            0 | 1 => FIXED_OUTPUTS[idx],
+            _ => &self.groups[idx - FIXED_OUTPUTS.len()],
+        }
+    }
+
+    /// Whether the pattern matches the text.
+    pub(super) fn matched(&self) -> bool {
+        self.regex().is_ok_and(|regex| regex.is_match(&self.text))
+    }
+
+    /// Full match or capture group of the first match, empty if there is none.
+    pub(super) fn string_out(&self, idx: usize) -> String {
+        let Ok(regex) = self.regex() else {
+            return String::new();
+        };
+        let Some(captures) = regex.captures(&self.text) else {
+            return String::new();
+        };
+
+        let group = match idx {
+            0 => unreachable!("Output `matched` is a bool"),
+            1 => captures.get(0),
+            _ => {
+                let name = &self.groups[idx - FIXED_OUTPUTS.len()];
+                match name.parse() {
+                    Ok(group) => captures.get(group),
+                    Err(_) => captures.name(name),
+                }
+            }
+        };
+        group.map_or_else(String::new, |group| group.as_str().to_owned())
+    }
+}
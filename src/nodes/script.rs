@@ -0,0 +1,505 @@
+//! Script node running Rhai code, for logic that doesn't fit in an expression.
+//!
+//! Inputs and outputs are declared in comments at the top of the script,
+//! with an optional type after the name:
+//!
+//! ```text
+//! // in: count, prefix: string
+//! // out: label: string
+//! let label = prefix;
+//! for i in 0..count { label += i; }
+//! ```
+//!
+//! Inputs are variables in scope and outputs are read from variables of the same name.
+//! Lines written with `print` and `debug` are shown under the node.
+//! Scripts can't reach files or the network, and they stop after [`MAX_OPERATIONS`].
+
+use std::cell::RefCell;
+
+use rhai::{Dynamic, Engine, Scope};
+
+use super::{
+    kind::{Port, PortType},
+    PinCompat, Value,
+};
+
+/// Operations a script may run before it is stopped, so that loops can't hang the UI.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Lines of `print` and `debug` output kept from a run.
+const MAX_PRINTED: usize = 20;
+
+/// Script of new nodes.
+const DEFAULT_TEXT: &str = "// in: x\n// out: y\nlet y = x * 2;\n";
+
+thread_local! {
+    static ENGINE: Engine = engine();
+    /// Output of the script being run, taken when it finishes.
+    static PRINTED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(64)
+        .set_max_string_size(1 << 20)
+        .set_max_array_size(1 << 16)
+        .set_max_map_size(1 << 16)
+        .on_print(|text| print(text.to_owned()))
+        .on_debug(|text, _, pos| print(with_line(pos, text)));
+    engine
+}
+
+/// Keeps a line of output instead of writing it to stdout, which the web build doesn't have.
+fn print(line: String) {
+    PRINTED.with_borrow_mut(|printed| {
+        if printed.len() < MAX_PRINTED {
+            printed.push(line);
+        }
+    });
+}
+
+/// Inputs and outputs declared in the leading comments of `text`.
+fn parse_header(text: &str) -> Result<(Vec<Port>, Vec<Port>), String> {
+    let mut ins = Vec::new();
+    let mut outs = Vec::new();
+    for (idx, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some(comment) = line.strip_prefix("//") else {
+            break;
+        };
+        let comment = comment.trim();
+        let (is_input, decls) = if let Some(decls) = comment.strip_prefix("in:") {
+            (true, decls)
+        } else if let Some(decls) = comment.strip_prefix("out:") {
+            (false, decls)
+        } else {
+            continue;
+        };
+
+        for decl in decls
+            .split(',')
+            .map(str::trim)
+            .filter(|decl| !decl.is_empty())
+        {
+            let port = parse_port(decl).map_err(|err| format!("Line {}: {err}", idx + 1))?;
+            if ins
+                .iter()
+                .chain(&outs)
+                .any(|other: &Port| other.name == port.name)
+            {
+                return Err(format!(
+                    "Line {}: `{}` is declared twice",
+                    idx + 1,
+                    port.name
+                ));
+            }
+            if is_input {
+                ins.push(port);
+            } else {
+                outs.push(port);
+            }
+        }
+    }
+    Ok((ins, outs))
+}
+
+/// Parses `name` or `name: type`.
+fn parse_port(decl: &str) -> Result<Port, String> {
+    let (name, ty) = match decl.split_once(':') {
+        Some((name, ty)) => (name.trim(), ty.trim()),
+        None => (decl, PortType::Number.name()),
+    };
+
+    let mut chars = name.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("Invalid name `{name}`"));
+    }
+
+    let ty = PortType::ALL
+        .into_iter()
+        .find(|port_type| port_type.name() == ty)
+        .ok_or_else(|| {
+            let names = PortType::ALL.map(PortType::name);
+            format!("Unknown type `{ty}`, expected one of {}", names.join(", "))
+        })?;
+
+    Ok(Port {
+        name: name.to_owned(),
+        ty,
+    })
+}
+
+/// Outputs or error of the last run, reused while the text and inputs stay the same.
+#[derive(Clone)]
+struct Run {
+    text: String,
+    inputs: Vec<Value>,
+    outputs: Result<Vec<Value>, String>,
+    printed: Vec<String>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct ScriptNode {
+    pub(super) text: String,
+    /// Declared inputs and outputs, kept while the header is invalid so that wires survive editing.
+    pub(super) ins: Vec<Port>,
+    pub(super) outs: Vec<Port>,
+    /// Values of the inputs declared in the header, in order.
+    pub(super) inputs: Vec<Value>,
+    /// Error in the header as of the last [`ScriptNode::reparse`].
+    #[serde(default)]
+    header_error: Option<String>,
+    #[serde(skip)]
+    cache: RefCell<Option<Run>>,
+}
+
+impl ScriptNode {
+    pub fn new() -> Self {
+        ScriptNode::with_text(DEFAULT_TEXT.to_owned())
+    }
+
+    /// Creates node with the ports declared in `text`, its inputs set to their initial values.
+    pub fn with_text(text: String) -> Self {
+        let mut node = ScriptNode {
+            text,
+            ins: Vec::new(),
+            outs: Vec::new(),
+            inputs: Vec::new(),
+            header_error: None,
+            cache: RefCell::new(None),
+        };
+        node.reparse();
+        node
+    }
+
+    /// Reads the header again after the text changed, updating the ports if it parses
+    /// and keeping its error otherwise.
+    ///
+    /// Inputs keep their values while their name and type stay the same.
+    pub(super) fn reparse(&mut self) {
+        let (ins, outs) = match parse_header(&self.text) {
+            Ok(ports) => ports,
+            Err(err) => {
+                self.header_error = Some(err);
+                return;
+            }
+        };
+        self.header_error = None;
+        self.inputs = ins
+            .iter()
+            .map(|port| {
+                self.ins
+                    .iter()
+                    .position(|old| old == port)
+                    .and_then(|idx| self.inputs.get(idx).cloned())
+                    .unwrap_or_else(|| port.ty.initial())
+            })
+            .collect();
+        self.ins = ins;
+        self.outs = outs;
+    }
+
+    /// Keys of the inputs for [`super::rebind_inputs`].
+    pub(super) fn in_keys(&self) -> Vec<String> {
+        self.ins.iter().map(Port::key).collect()
+    }
+
+    /// Keys of the outputs for [`super::rebind_outputs`].
+    pub(super) fn out_keys(&self) -> Vec<String> {
+        self.outs.iter().map(Port::key).collect()
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        &self.ins[idx].name
+    }
+
+    pub(super) fn label_out(&self, idx: usize) -> &str {
+        &self.outs[idx].name
+    }
+
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        self.ins[idx].ty.in_compat()
+    }
+
+    /// None for scripts without outputs, which are still drawn with the color of the first.
+    pub(super) fn out_compat(&self, idx: usize) -> PinCompat {
+        self.outs.get(idx).map_or(0, |port| port.ty.out_compat())
+    }
+
+    /// Header, compile or runtime error with its line.
+    pub(super) fn error(&self) -> Option<String> {
+        self.run().outputs.as_ref().err().cloned()
+    }
+
+    /// Lines written with `print` and `debug`, up to [`MAX_PRINTED`].
+    pub(super) fn printed(&self) -> Vec<String> {
+        self.run().printed.clone()
+    }
+
+    pub(super) fn value_out(&self, idx: usize) -> Value {
+        self.run()
+            .outputs
+            .as_ref()
+            .ok()
+            .and_then(|outputs| outputs.get(idx).cloned())
+            .unwrap_or_else(|| self.outs[idx].ty.initial())
+    }
+
+    /// Last run for the current text and inputs, running the script if they changed.
+    fn run(&self) -> std::cell::Ref<'_, Run> {
+        let current = self
+            .cache
+            .borrow()
+            .as_ref()
+            .is_some_and(|run| run.text == self.text && run.inputs == self.inputs);
+        if !current {
+            let outputs = self.eval();
+            *self.cache.borrow_mut() = Some(Run {
+                text: self.text.clone(),
+                inputs: self.inputs.clone(),
+                outputs,
+                printed: PRINTED.take(),
+            });
+        }
+        std::cell::Ref::map(self.cache.borrow(), |run| {
+            run.as_ref().expect("Script has run above")
+        })
+    }
+
+    fn eval(&self) -> Result<Vec<Value>, String> {
+        if let Some(ref err) = self.header_error {
+            return Err(err.clone());
+        }
+
+        let mut scope = Scope::new();
+        for (port, value) in self.ins.iter().zip(&self.inputs) {
+            let value = match (port.ty, value) {
+                (PortType::Json, Value::String(text)) => serde_json::from_str(text)
+                    .map(|json| json_to_dynamic(&json))
+                    .map_err(|err| format!("Input `{}` isn't JSON: {err}", port.name))?,
+                _ => to_dynamic(value),
+            };
+            scope.push_dynamic(port.name.clone(), value);
+        }
+        for port in &self.outs {
+            scope.push_dynamic(port.name.clone(), Dynamic::UNIT);
+        }
+
+        ENGINE.with(|engine| {
+            let ast = engine.compile(&self.text).map_err(|err| {
+                let rhai::ParseError(message, pos) = err;
+                with_line(pos, &message.to_string())
+            })?;
+            engine
+                .run_ast_with_scope(&mut scope, &ast)
+                .map_err(|mut err| {
+                    let pos = err.take_position();
+                    with_line(pos, &err.to_string())
+                })
+        })?;
+
+        self.outs
+            .iter()
+            .map(|port| {
+                let value = scope
+                    .get_value::<Dynamic>(&port.name)
+                    .unwrap_or(Dynamic::UNIT);
+                from_dynamic(value, port.ty).map_err(|err| format!("Output `{}` {err}", port.name))
+            })
+            .collect()
+    }
+}
+
+fn with_line(pos: rhai::Position, message: &str) -> String {
+    match pos.line() {
+        Some(line) => format!("Line {line}: {message}"),
+        None => message.to_owned(),
+    }
+}
+
+/// Numbers are floats, vectors and colors are arrays of them and JSON objects are maps.
+fn to_dynamic(value: &Value) -> Dynamic {
+    match *value {
+        Value::Number(value) => Dynamic::from_float(value),
+        Value::String(ref value) => value.clone().into(),
+        Value::Bool(value) => Dynamic::from_bool(value),
+        Value::Vector(ref components) => components
+            .iter()
+            .map(|&component| Dynamic::from_float(component))
+            .collect::<Vec<_>>()
+            .into(),
+        Value::Color(color) => color
+            .iter()
+            .map(|&component| Dynamic::from_float(component))
+            .collect::<Vec<_>>()
+            .into(),
+        Value::List(ref items) => items.iter().map(to_dynamic).collect::<Vec<_>>().into(),
+        Value::Json(ref json) => json_to_dynamic(json),
+        Value::Image(_) => Dynamic::UNIT,
+    }
+}
+
+fn json_to_dynamic(json: &serde_json::Value) -> Dynamic {
+    match *json {
+        serde_json::Value::Null => Dynamic::UNIT,
+        serde_json::Value::Bool(value) => Dynamic::from_bool(value),
+        serde_json::Value::Number(ref number) => match number.as_i64() {
+            Some(value) => Dynamic::from_int(value),
+            None => Dynamic::from_float(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(ref value) => value.clone().into(),
+        serde_json::Value::Array(ref items) => {
+            items.iter().map(json_to_dynamic).collect::<Vec<_>>().into()
+        }
+        serde_json::Value::Object(ref object) => Dynamic::from_map(
+            object
+                .iter()
+                .map(|(key, value)| (key.into(), json_to_dynamic(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Converts the value of an output variable to its declared type.
+fn from_dynamic(value: Dynamic, ty: PortType) -> Result<Value, String> {
+    if value.is_unit() && ty != PortType::Json {
+        return Err("isn't set".to_owned());
+    }
+    let mismatch = |value: &Dynamic| format!("is {}, expected {}", value.type_name(), ty.name());
+
+    match ty {
+        PortType::Number => number(&value)
+            .map(Value::Number)
+            .ok_or_else(|| mismatch(&value)),
+        // Anything can be shown as a string.
+        PortType::String => Ok(Value::String(value.to_string())),
+        PortType::Bool => value
+            .as_bool()
+            .map(Value::Bool)
+            .map_err(|_| mismatch(&value)),
+        PortType::Vec2 | PortType::Vec3 | PortType::Vec4 | PortType::Color => {
+            let len = match ty {
+                PortType::Vec2 => 2,
+                PortType::Vec3 => 3,
+                _ => 4,
+            };
+            let components = value
+                .clone()
+                .into_array()
+                .ok()
+                .and_then(|items| items.iter().map(number).collect::<Option<Vec<_>>>())
+                .filter(|components| components.len() == len)
+                .ok_or_else(|| format!("is {value}, expected {len} numbers"))?;
+            Ok(match ty {
+                PortType::Color => {
+                    Value::Color([components[0], components[1], components[2], components[3]])
+                }
+                _ => Value::Vector(components),
+            })
+        }
+        PortType::List => {
+            let items = value.clone().into_array().map_err(|_| mismatch(&value))?;
+            items
+                .into_iter()
+                .map(|item| {
+                    if let Some(value) = number(&item) {
+                        Ok(Value::Number(value))
+                    } else if let Ok(value) = item.as_bool() {
+                        Ok(Value::Bool(value))
+                    } else if item.is_string() {
+                        Ok(Value::String(item.to_string()))
+                    } else {
+                        Err(format!(
+                            "has {} item, expected numbers, strings or bools",
+                            item.type_name()
+                        ))
+                    }
+                })
+                .collect::<Result<_, _>>()
+                .map(Value::List)
+        }
+        PortType::Json => dynamic_to_json(&value).map(Value::Json),
+    }
+}
+
+/// Integers and floats as a number.
+fn number(value: &Dynamic) -> Option<f64> {
+    value
+        .as_float()
+        .ok()
+        .or_else(|| value.as_int().ok().map(|value| value as f64))
+}
+
+fn dynamic_to_json(value: &Dynamic) -> Result<serde_json::Value, String> {
+    if value.is_unit() {
+        Ok(serde_json::Value::Null)
+    } else if let Ok(value) = value.as_bool() {
+        Ok(serde_json::Value::Bool(value))
+    } else if let Ok(value) = value.as_int() {
+        Ok(value.into())
+    } else if let Ok(value) = value.as_float() {
+        Ok(serde_json::Number::from_f64(value).map_or(serde_json::Value::Null, Into::into))
+    } else if value.is_string() {
+        Ok(serde_json::Value::String(value.to_string()))
+    } else if value.is_array() {
+        value
+            .clone()
+            .into_array()
+            .unwrap_or_default()
+            .iter()
+            .map(dynamic_to_json)
+            .collect::<Result<_, _>>()
+            .map(serde_json::Value::Array)
+    } else if value.is_map() {
+        value
+            .clone()
+            .cast::<rhai::Map>()
+            .iter()
+            .map(|(key, value)| Ok((key.to_string(), dynamic_to_json(value)?)))
+            .collect::<Result<_, _>>()
+            .map(serde_json::Value::Object)
+    } else {
+        Err(format!("has {}, which isn't JSON", value.type_name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_printed_lines() {
+        let node = ScriptNode::with_text(
+            "// out: y\nprint(\"start\");\nlet y = 1.0;\ndebug(y);\nfor i in 0..100 { print(i); }"
+                .to_owned(),
+        );
+        assert!(node.error().is_none());
+        let printed = node.printed();
+        assert_eq!(printed.len(), MAX_PRINTED);
+        assert_eq!(printed[..3], ["start", "Line 4: 1.0", "0"]);
+    }
+
+    #[test]
+    fn reports_header_errors() {
+        let mut node = ScriptNode::new();
+        node.text = "// in: x: text\n// out: y\nlet y = 1.0;".to_owned();
+        node.reparse();
+        let err = node.error().expect("header is invalid");
+        assert!(err.starts_with("Line 1: Unknown type `text`"), "{err}");
+        assert_eq!(node.label_in(0), "x");
+
+        node.text = "// out: y\nlet y = 1.0;".to_owned();
+        node.reparse();
+        assert!(node.error().is_none());
+        assert!(matches!(node.value_out(0), Value::Number(1.0)));
+    }
+}
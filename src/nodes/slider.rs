@@ -0,0 +1,94 @@
+//! Number source constrained to a range.
+
+use super::units::{self, Quantity};
+
+/// Number edited with a slider, kept within its range and on its steps.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct SliderNode {
+    pub(super) value: f64,
+    pub(super) min: f64,
+    pub(super) max: f64,
+    /// Distance between allowed values counted from `min`, any value if zero.
+    pub(super) step: f64,
+    /// Slides over orders of magnitude instead of evenly.
+    pub(super) logarithmic: bool,
+    /// Unit shown after the value, such as `m` or `%`.
+    pub(super) unit: String,
+}
+
+impl SliderNode {
+    pub fn new() -> Self {
+        SliderNode {
+            value: 0.0,
+            min: 0.0,
+            max: 1.0,
+            step: 0.0,
+            logarithmic: false,
+            unit: String::new(),
+        }
+    }
+
+    /// Closest value within the range and on a step.
+    pub(super) fn constrain(&self, value: f64) -> f64 {
+        let (low, high) = (self.min.min(self.max), self.min.max(self.max));
+        let mut value = value.clamp(low, high);
+        if self.step > 0.0 {
+            value = low + ((value - low) / self.step).round() * self.step;
+            if value > high {
+                value -= self.step;
+            }
+        }
+        value
+    }
+
+    pub(super) fn value_out(&self) -> f64 {
+        self.constrain(self.value)
+    }
+
+    /// Value in base units if the unit is known, as a plain number otherwise.
+    pub(super) fn quantity_out(&self) -> Quantity {
+        units::quantity(self.value_out(), &self.unit)
+    }
+
+    /// Shows the slider, constraining what was dragged or typed.
+    pub(super) fn show_output(&mut self, ui: &mut egui::Ui) {
+        let mut slider = egui::Slider::new(&mut self.value, self.min..=self.max)
+            .logarithmic(self.logarithmic)
+            .clamping(egui::SliderClamping::Always);
+        if !self.unit.is_empty() {
+            slider = slider.suffix(format!(" {}", self.unit));
+        }
+        if ui.add(slider).changed() {
+            self.value = self.constrain(self.value);
+        }
+    }
+
+    /// Shows the range, step, scale and unit.
+    pub(super) fn show_body(&mut self, ui: &mut egui::Ui, id_salt: impl std::hash::Hash) {
+        egui::CollapsingHeader::new("Range")
+            .id_salt(id_salt)
+            .show(ui, |ui| {
+                egui::Grid::new("range").show(ui, |ui| {
+                    ui.label("Min");
+                    ui.add(egui::DragValue::new(&mut self.min).speed(0.1));
+                    ui.end_row();
+                    ui.label("Max");
+                    ui.add(egui::DragValue::new(&mut self.max).speed(0.1));
+                    ui.end_row();
+                    ui.label("Step");
+                    ui.add(
+                        egui::DragValue::new(&mut self.step)
+                            .speed(0.01)
+                            .range(0.0..=f64::INFINITY),
+                    )
+                    .on_hover_text("Any value if zero");
+                    ui.end_row();
+                    ui.label("Unit");
+                    ui.add(egui::TextEdit::singleline(&mut self.unit).desired_width(40.0));
+                    ui.end_row();
+                });
+                ui.checkbox(&mut self.logarithmic, "Logarithmic");
+            });
+        self.value = self.constrain(self.value);
+    }
+}
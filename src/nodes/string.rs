@@ -0,0 +1,231 @@
+//! String manipulation nodes.
+
+use super::{PinCompat, Value, PIN_NUM, PIN_STR, PIN_VALUE};
+
+node_ops! {
+    pub enum StringOp {
+        /// Joins all inputs, always keeping one spare input to connect to.
+        Concat: "Concat", "Joins strings of all inputs";
+        /// Fills `{}` and `{name}` placeholders of the template.
+        Format: "Format",
+            "Fills {} and {name} placeholders of the template with input for each placeholder";
+        Replace: "Replace", "Replaces all matches of a string with another";
+        /// Takes `Length` characters starting from the character at `Start`.
+        Substring: "Substring", "Outputs part of the string by character position";
+        Upper: "Upper", "Converts string to upper case";
+        Lower: "Lower", "Converts string to lower case";
+        Trim: "Trim", "Removes whitespace around string";
+        /// Number of characters.
+        Length: "Length", "Outputs number of characters in string";
+        /// Formats number with given number of decimal places.
+        NumberToString: "Number to String", "Formats number with given precision";
+    }
+}
+
+impl StringOp {
+    /// Labels and initial values of fixed inputs.
+    fn inputs(self) -> Vec<(&'static str, Value)> {
+        let text = || ("Text", Value::String(String::new()));
+        match self {
+            StringOp::Concat => vec![("", Value::String(String::new()))],
+            StringOp::Format => vec![("Template", Value::String("{}".to_owned()))],
+            StringOp::Replace => vec![
+                text(),
+                ("From", Value::String(String::new())),
+                ("To", Value::String(String::new())),
+            ],
+            StringOp::Substring => vec![
+                text(),
+                ("Start", Value::Number(0.0)),
+                ("Length", Value::Number(1.0)),
+            ],
+            StringOp::Upper | StringOp::Lower | StringOp::Trim | StringOp::Length => vec![text()],
+            StringOp::NumberToString => vec![
+                ("Value", Value::Number(0.0)),
+                ("Precision", Value::Number(3.0)),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct StringNode {
+    pub(super) op: StringOp,
+    /// Strings and numbers the operation reads, starting with the template for Format.
+    pub(super) inputs: Vec<Value>,
+    /// Placeholder names of the Format template, one per input after the template.
+    /// Positional placeholders are named by their index.
+    #[serde(default)]
+    pub(super) placeholders: Vec<String>,
+}
+
+impl StringNode {
+    pub fn new(op: StringOp) -> Self {
+        let mut node = StringNode {
+            op,
+            inputs: op.inputs().into_iter().map(|(_, value)| value).collect(),
+            placeholders: Vec::new(),
+        };
+        if op == StringOp::Format {
+            node.placeholders = placeholders(node.string_in(0));
+            node.inputs
+                .resize(1 + node.placeholders.len(), Value::String(String::new()));
+        }
+        node
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        match self.op {
+            StringOp::Concat => "",
+            StringOp::Format if idx > 0 => &self.placeholders[idx - 1],
+            op => op.inputs()[idx].0,
+        }
+    }
+
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        match self.op {
+            StringOp::Format if idx > 0 => PIN_VALUE,
+            _ => self.inputs[idx].compat(),
+        }
+    }
+
+    pub(super) const fn out_compat(&self) -> PinCompat {
+        match self.op {
+            StringOp::Length => PIN_NUM,
+            _ => PIN_STR,
+        }
+    }
+
+    pub(super) fn template(&self) -> &str {
+        self.string_in(0)
+    }
+
+    fn string_in(&self, idx: usize) -> &str {
+        match self.inputs[idx] {
+            Value::String(ref value) => value,
+            _ => "",
+        }
+    }
+
+    fn number_in(&self, idx: usize) -> f64 {
+        match self.inputs[idx] {
+            Value::Number(value) => value,
+            _ => 0.0,
+        }
+    }
+
+    pub(super) fn number_out(&self) -> f64 {
+        match self.op {
+            StringOp::Length => self.string_in(0).chars().count() as f64,
+            _ => unreachable!("Only Length outputs a number"),
+        }
+    }
+
+    pub(super) fn string_out(&self) -> String {
+        match self.op {
+            StringOp::Concat => (0..self.inputs.len())
+                .map(|idx| self.string_in(idx))
+                .collect(),
+            StringOp::Format => {
+                let mut out = String::new();
+                for part in parse_template(self.string_in(0)) {
+                    match part {
+                        TemplatePart::Text(text) => out.push_str(&text),
+                        TemplatePart::Placeholder(name) => {
+                            let idx = self.placeholders.iter().position(|p| *p == name);
+                            if let Some(idx) = idx {
+                                out.push_str(&self.inputs[idx + 1].string());
+                            }
+                        }
+                    }
+                }
+                out
+            }
+            StringOp::Replace => {
+                let from = self.string_in(1);
+                if from.is_empty() {
+                    self.string_in(0).to_owned()
+                } else {
+                    self.string_in(0).replace(from, self.string_in(2))
+                }
+            }
+            StringOp::Substring => {
+                let start = self.number_in(1).max(0.0) as usize;
+                let len = self.number_in(2).max(0.0) as usize;
+                self.string_in(0).chars().skip(start).take(len).collect()
+            }
+            StringOp::Upper => self.string_in(0).to_uppercase(),
+            StringOp::Lower => self.string_in(0).to_lowercase(),
+            StringOp::Trim => self.string_in(0).trim().to_owned(),
+            StringOp::Length => unreachable!("Length outputs a number"),
+            StringOp::NumberToString => {
+                let precision = self.number_in(1).clamp(0.0, 17.0) as usize;
+                format!("{:.*}", precision, self.number_in(0))
+            }
+        }
+    }
+}
+
+enum TemplatePart {
+    Text(String),
+    Placeholder(String),
+}
+
+/// Splits a Format template into text and placeholders.
+///
+/// `{{` and `}}` escape braces, and unclosed braces are kept as text.
+fn parse_template(template: &str) -> Vec<TemplatePart> {
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut positional = 0;
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let rest = chars.clone().collect::<String>();
+                let Some(end) = rest.find('}') else {
+                    text.push('{');
+                    continue;
+                };
+
+                let mut name = rest[..end].trim().to_owned();
+                if name.is_empty() {
+                    name = positional.to_string();
+                    positional += 1;
+                }
+                for _ in rest[..=end].chars() {
+                    chars.next();
+                }
+
+                parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                parts.push(TemplatePart::Placeholder(name));
+            }
+            c => text.push(c),
+        }
+    }
+
+    parts.push(TemplatePart::Text(text));
+    parts
+}
+
+/// Returns unique placeholder names of a Format template, in order of appearance.
+pub(super) fn placeholders(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for part in parse_template(template) {
+        if let TemplatePart::Placeholder(name) = part {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
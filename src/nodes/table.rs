@@ -0,0 +1,329 @@
+//! CSV table node.
+
+use std::cell::OnceCell;
+use std::sync::{Arc, Mutex};
+
+use super::Value;
+
+/// Data rows shown in the node body.
+const PREVIEW_ROWS: usize = 5;
+
+/// Delimiters offered in the node body with their names.
+const DELIMITERS: [(u8, &str); 4] = [
+    (b',', "Comma"),
+    (b';', "Semicolon"),
+    (b'\t', "Tab"),
+    (b'|', "Pipe"),
+];
+
+/// Whether the first row holds column names.
+#[derive(Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
+pub enum Header {
+    /// Header if the first row has no numbers above columns of numbers.
+    #[default]
+    Auto,
+    Yes,
+    No,
+}
+
+impl Header {
+    const ALL: [Header; 3] = [Header::Auto, Header::Yes, Header::No];
+
+    const fn name(self) -> &'static str {
+        match self {
+            Header::Auto => "Detect header",
+            Header::Yes => "Header",
+            Header::No => "No header",
+        }
+    }
+}
+
+/// Parsed CSV text.
+#[derive(Clone)]
+struct Table {
+    /// Column names from the header row, numbered where it has none.
+    names: Vec<String>,
+    /// Values of each column, numbers if all non-empty cells are numbers.
+    columns: Vec<Vec<Value>>,
+    /// First data rows as written.
+    preview: Vec<Vec<String>>,
+    rows: usize,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct TableNode {
+    pub(super) text: String,
+    pub(super) delimiter: u8,
+    pub(super) header: Header,
+    /// Output name of each column, from the header row or numbered.
+    pub(super) columns: Vec<String>,
+    #[serde(skip)]
+    table: OnceCell<Result<Table, String>>,
+    /// File text picked in a dialog, taken on the next frame.
+    #[serde(skip)]
+    loaded: Arc<Mutex<Option<Result<String, String>>>>,
+    #[serde(skip)]
+    load_error: Option<String>,
+}
+
+impl TableNode {
+    pub fn new() -> Self {
+        TableNode {
+            text: String::new(),
+            delimiter: b',',
+            header: Header::Auto,
+            columns: Vec::new(),
+            table: OnceCell::new(),
+            loaded: Arc::default(),
+            load_error: None,
+        }
+    }
+
+    /// Creates node with parsed `text`.
+    pub fn with_text(text: String, delimiter: u8, header: Header) -> Self {
+        let mut node = TableNode {
+            text,
+            delimiter,
+            header,
+            ..TableNode::new()
+        };
+        node.reparse();
+        node
+    }
+
+    fn table(&self) -> Result<&Table, &str> {
+        self.table
+            .get_or_init(|| parse(&self.text, self.delimiter, self.header))
+            .as_ref()
+            .map_err(String::as_str)
+    }
+
+    /// Parses the text again after it or the options changed, updating columns if it parses.
+    ///
+    /// Columns are kept while the text is invalid so that wires survive editing.
+    pub(super) fn reparse(&mut self) {
+        self.table = OnceCell::new();
+        if let Ok(table) = self.table() {
+            self.columns = table.names.clone();
+        }
+    }
+
+    /// Parse or file error.
+    pub(super) fn error(&self) -> Option<&str> {
+        self.load_error.as_deref().or_else(|| self.table().err())
+    }
+
+    /// Column as a list, empty if the text doesn't parse.
+    pub(super) fn value_out(&self, idx: usize) -> Value {
+        let column = self
+            .table()
+            .ok()
+            .and_then(|table| table.columns.get(idx))
+            .cloned()
+            .unwrap_or_default();
+        Value::List(column)
+    }
+
+    /// Shows options, file button, text editor and preview.
+    /// Returns whether the columns may have changed.
+    pub(super) fn show_body(
+        &mut self,
+        ui: &mut egui::Ui,
+        id_salt: impl std::hash::Hash + Copy,
+    ) -> bool {
+        let mut changed = false;
+
+        let loaded = self.loaded.lock().ok().and_then(|mut loaded| loaded.take());
+        match loaded {
+            Some(Ok(text)) => {
+                self.text = text;
+                self.load_error = None;
+                changed = true;
+            }
+            Some(Err(err)) => self.load_error = Some(err),
+            None => {}
+        }
+
+        ui.horizontal(|ui| {
+            let delimiter_name = DELIMITERS
+                .iter()
+                .find(|&&(delimiter, _)| delimiter == self.delimiter)
+                .map_or("Other", |&(_, name)| name);
+            egui::ComboBox::from_id_salt((id_salt, "delimiter"))
+                .selected_text(delimiter_name)
+                .show_ui(ui, |ui| {
+                    for (delimiter, name) in DELIMITERS {
+                        changed |= ui
+                            .selectable_value(&mut self.delimiter, delimiter, name)
+                            .changed();
+                    }
+                });
+            egui::ComboBox::from_id_salt((id_salt, "header"))
+                .selected_text(self.header.name())
+                .show_ui(ui, |ui| {
+                    for header in Header::ALL {
+                        changed |= ui
+                            .selectable_value(&mut self.header, header, header.name())
+                            .changed();
+                    }
+                });
+            if ui.button("Open…").clicked() {
+                self.pick_file(ui.ctx());
+            }
+        });
+
+        egui::CollapsingHeader::new("CSV")
+            .id_salt((id_salt, "text"))
+            .show(ui, |ui| {
+                changed |= egui::TextEdit::multiline(&mut self.text)
+                    .code_editor()
+                    .desired_rows(4)
+                    .show(ui)
+                    .response
+                    .changed();
+            });
+
+        if let Ok(table) = self.table() {
+            egui::Grid::new((id_salt, "preview"))
+                .striped(true)
+                .show(ui, |ui| {
+                    for name in &self.columns {
+                        ui.strong(name);
+                    }
+                    ui.end_row();
+                    for row in &table.preview {
+                        for cell in row {
+                            ui.label(cell);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if table.rows > table.preview.len() {
+                ui.weak(format!("{} more rows", table.rows - table.preview.len()));
+            }
+        }
+
+        if changed {
+            self.reparse();
+        }
+        changed
+    }
+
+    /// Opens a file dialog and reads the picked file.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn pick_file(&mut self, _ctx: &egui::Context) {
+        let Some(path) = file_dialog().pick_file() else {
+            return;
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Failed to read {}: {err}", path.display()));
+        if let Ok(mut loaded) = self.loaded.lock() {
+            *loaded = Some(text);
+        }
+    }
+
+    /// Opens an upload dialog, the file is read once it is picked.
+    #[cfg(target_arch = "wasm32")]
+    fn pick_file(&mut self, ctx: &egui::Context) {
+        let loaded = Arc::clone(&self.loaded);
+        let ctx = ctx.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            let Some(file) = file_dialog().pick_file().await else {
+                return;
+            };
+            let text = String::from_utf8(file.read().await)
+                .map_err(|_| format!("{} is not UTF-8 text", file.file_name()));
+            if let Ok(mut loaded) = loaded.lock() {
+                *loaded = Some(text);
+            }
+            ctx.request_repaint();
+        });
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn file_dialog() -> rfd::FileDialog {
+    rfd::FileDialog::new().add_filter("CSV", &["csv", "tsv", "txt"])
+}
+
+#[cfg(target_arch = "wasm32")]
+fn file_dialog() -> rfd::AsyncFileDialog {
+    rfd::AsyncFileDialog::new().add_filter("CSV", &["csv", "tsv", "txt"])
+}
+
+fn is_number(cell: &str) -> bool {
+    cell.trim().parse::<f64>().is_ok()
+}
+
+fn read_rows(text: &str, delimiter: u8) -> Result<Vec<Vec<String>>, String> {
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes())
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_owned).collect())
+                .map_err(|err| err.to_string())
+        })
+        .collect()
+}
+
+/// Whether the first row has no numbers while a cell below it in the same column has.
+fn detect_header(rows: &[Vec<String>]) -> bool {
+    let Some((first, rest)) = rows.split_first() else {
+        return false;
+    };
+    !first.iter().any(|cell| is_number(cell))
+        && (0..first.len()).any(|idx| {
+            rest.iter()
+                .any(|row| row.get(idx).is_some_and(|cell| is_number(cell)))
+        })
+}
+
+fn parse(text: &str, delimiter: u8, header: Header) -> Result<Table, String> {
+    let rows = read_rows(text, delimiter)?;
+    let header = match header {
+        Header::Auto => detect_header(&rows),
+        Header::Yes => !rows.is_empty(),
+        Header::No => false,
+    };
+    let (header_row, data) = match rows.split_first() {
+        Some((first, rest)) if header => (&first[..], rest),
+        _ => (&[][..], &rows[..]),
+    };
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+    let names = (0..width)
+        .map(|idx| match header_row.get(idx).map(|name| name.trim()) {
+            Some(name) if !name.is_empty() => name.to_owned(),
+            _ => format!("column{}", idx + 1),
+        })
+        .collect();
+
+    let columns = (0..width)
+        .map(|idx| {
+            let cells = data
+                .iter()
+                .map(|row| row.get(idx).map_or("", |cell| cell.trim()))
+                .collect::<Vec<_>>();
+            let numbers = cells.iter().any(|cell| !cell.is_empty())
+                && cells.iter().all(|cell| cell.is_empty() || is_number(cell));
+            cells
+                .into_iter()
+                .map(|cell| match numbers {
+                    true => Value::Number(cell.parse().unwrap_or(f64::NAN)),
+                    false => Value::String(cell.to_owned()),
+                })
+                .collect()
+        })
+        .collect();
+
+    Ok(Table {
+        names,
+        columns,
+        preview: data.iter().take(PREVIEW_ROWS).cloned().collect(),
+        rows: data.len(),
+    })
+}
@@ -0,0 +1,246 @@
+//! Time, frame, LFO and keyframe nodes driven by the transport.
+
+use std::f64::consts::TAU;
+
+use super::{PinCompat, Value, PIN_NUM};
+
+/// Time step of a single frame when stepping while paused.
+const STEP: f64 = 1.0 / 60.0;
+
+/// Playback time the animated nodes read, set on them each frame.
+#[derive(Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+pub struct Clock {
+    /// Seconds since reset.
+    pub time: f64,
+    /// Frames since reset.
+    pub frame: u64,
+}
+
+/// Play, pause, step and reset of the clock.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Transport {
+    pub clock: Clock,
+    pub playing: bool,
+}
+
+impl Transport {
+    /// Advances the clock while playing, asking for the next frame.
+    pub fn advance(&mut self, ctx: &egui::Context) {
+        if self.playing {
+            self.step(f64::from(ctx.input(|input| input.stable_dt)));
+            ctx.request_repaint();
+        }
+    }
+
+    fn step(&mut self, dt: f64) {
+        self.clock.time += dt;
+        self.clock.frame += 1;
+    }
+
+    /// Shows the transport buttons with the time and frame.
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let play = if self.playing { "⏸" } else { "▶" };
+        if ui
+            .button(play)
+            .on_hover_text(if self.playing { "Pause" } else { "Play" })
+            .clicked()
+        {
+            self.playing = !self.playing;
+        }
+        if ui
+            .add_enabled(!self.playing, egui::Button::new("⏭"))
+            .on_hover_text("Step one frame")
+            .clicked()
+        {
+            self.step(STEP);
+        }
+        if ui.button("⏮").on_hover_text("Reset").clicked() {
+            self.clock = Clock::default();
+        }
+        ui.monospace(format!(
+            "{:.2} s  frame {}",
+            self.clock.time, self.clock.frame
+        ));
+    }
+}
+
+node_ops! {
+    pub enum TimeOp {
+        Time: "Time", "Outputs seconds since the transport was reset";
+        Frame: "Frame", "Outputs frames since the transport was reset";
+        SineLfo: "Sine LFO",
+            "Oscillates smoothly between offset - amplitude and offset + amplitude";
+        SawLfo: "Saw LFO", "Ramps from offset - amplitude to offset + amplitude, then jumps back";
+        SquareLfo: "Square LFO", "Alternates between offset + amplitude and offset - amplitude";
+    }
+}
+
+impl TimeOp {
+    /// Labels and initial values of the inputs.
+    fn inputs(self) -> Vec<(&'static str, Value)> {
+        match self {
+            TimeOp::Time | TimeOp::Frame => Vec::new(),
+            TimeOp::SineLfo | TimeOp::SawLfo | TimeOp::SquareLfo => vec![
+                ("Frequency", Value::Number(1.0)),
+                ("Amplitude", Value::Number(1.0)),
+                ("Offset", Value::Number(0.0)),
+                ("Phase", Value::Number(0.0)),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct TimeNode {
+    pub(super) op: TimeOp,
+    /// Frequency, amplitude, offset and phase of the LFOs, none for Time and Frame.
+    pub(super) inputs: Vec<Value>,
+    #[serde(skip)]
+    pub(super) clock: Clock,
+}
+
+impl TimeNode {
+    pub fn new(op: TimeOp) -> Self {
+        TimeNode {
+            op,
+            inputs: op.inputs().into_iter().map(|(_, value)| value).collect(),
+            clock: Clock::default(),
+        }
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        self.op.inputs()[idx].0
+    }
+
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        if idx < self.inputs.len() {
+            PIN_NUM
+        } else {
+            0
+        }
+    }
+
+    pub(super) fn value_out(&self) -> Value {
+        let number_in = |idx: usize| self.inputs[idx].number();
+        #[allow(clippy::cast_precision_loss)]
+        let value = match self.op {
+            TimeOp::Time => self.clock.time,
+            TimeOp::Frame => self.clock.frame as f64,
+            TimeOp::SineLfo | TimeOp::SawLfo | TimeOp::SquareLfo => {
+                // Position within the period, in 0..1.
+                let cycle = (number_in(0) * self.clock.time + number_in(3)).rem_euclid(1.0);
+                let wave = match self.op {
+                    TimeOp::SineLfo => (TAU * cycle).sin(),
+                    TimeOp::SawLfo => 2.0 * cycle - 1.0,
+                    _ if cycle < 0.5 => 1.0,
+                    _ => -1.0,
+                };
+                number_in(2) + number_in(1) * wave
+            }
+        };
+        Value::Number(value)
+    }
+}
+
+/// Curve through values at times, read at the clock time.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct KeyframesNode {
+    /// Time and value of each key, sorted by time.
+    pub(super) keys: Vec<[f64; 2]>,
+    /// Eases in and out of each key instead of moving linearly.
+    pub(super) smooth: bool,
+    /// Repeats the curve after the last key.
+    pub(super) looped: bool,
+    #[serde(skip)]
+    pub(super) clock: Clock,
+}
+
+impl KeyframesNode {
+    pub fn new() -> Self {
+        KeyframesNode {
+            keys: vec![[0.0, 0.0], [1.0, 1.0]],
+            smooth: false,
+            looped: false,
+            clock: Clock::default(),
+        }
+    }
+
+    /// Creates node with keys sorted by time.
+    pub fn with_keys(mut keys: Vec<[f64; 2]>, smooth: bool, looped: bool) -> Self {
+        keys.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        KeyframesNode {
+            keys,
+            smooth,
+            looped,
+            clock: Clock::default(),
+        }
+    }
+
+    /// Value at the clock time, holding the first and last values outside the keys.
+    pub(super) fn value_out(&self) -> Value {
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return Value::Number(0.0);
+        };
+
+        let mut time = self.clock.time;
+        if self.looped && last[0] > first[0] {
+            time = first[0] + (time - first[0]).rem_euclid(last[0] - first[0]);
+        }
+
+        let next = self.keys.partition_point(|key| key[0] <= time);
+        let value = match (
+            next.checked_sub(1).map(|idx| self.keys[idx]),
+            self.keys.get(next),
+        ) {
+            (Some([start, from]), Some(&[end, to])) => {
+                let mut t = (time - start) / (end - start);
+                if self.smooth {
+                    t = t * t * (3.0 - 2.0 * t);
+                }
+                from + (to - from) * t
+            }
+            (Some([_, value]), None) | (None, Some(&[_, value])) => value,
+            (None, None) => 0.0,
+        };
+        Value::Number(value)
+    }
+
+    /// Shows options and a row for each key.
+    pub(super) fn show_body(&mut self, ui: &mut egui::Ui, id_salt: impl std::hash::Hash) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.smooth, "Smooth");
+            ui.checkbox(&mut self.looped, "Loop");
+        });
+
+        let mut removed = None;
+        egui::Grid::new(id_salt).striped(true).show(ui, |ui| {
+            ui.strong("Time");
+            ui.strong("Value");
+            ui.end_row();
+            for (idx, key) in self.keys.iter_mut().enumerate() {
+                ui.add(egui::DragValue::new(&mut key[0]).speed(0.01));
+                ui.add(egui::DragValue::new(&mut key[1]).speed(0.01));
+                if ui.small_button("✖").on_hover_text("Remove key").clicked() {
+                    removed = Some(idx);
+                }
+                ui.end_row();
+            }
+        });
+        if let Some(idx) = removed {
+            self.keys.remove(idx);
+        }
+
+        if ui.button("Add key at current time").clicked() {
+            let Value::Number(value) = self.value_out() else {
+                unreachable!("Keyframes output numbers")
+            };
+            self.keys.push([self.clock.time, value]);
+        }
+
+        // Sorting while dragging a time would move the row under the pointer.
+        if ui.ctx().dragged_id().is_none() {
+            self.keys.sort_by(|a, b| a[0].total_cmp(&b[0]));
+        }
+    }
+}
@@ -0,0 +1,407 @@
+//! Units of measure and bounds carried by expression values.
+
+use super::{complex::Complex, interval::Bounds, numeric::Real};
+
+/// Names of the base units, in the order of [`Dim`] exponents.
+const BASE_UNITS: [&str; 5] = ["kg", "m", "s", "A", "K"];
+
+/// Dimension of a quantity as exponents of the base units kilogram, metre, second, ampere and kelvin.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Dim([i8; 5]);
+
+impl Dim {
+    /// Dimension of plain numbers.
+    pub const NONE: Dim = Dim([0; 5]);
+
+    /// Adds exponents of `other` times `sign`, or `None` if one doesn't fit.
+    const fn combine(self, other: Dim, sign: i8) -> Option<Dim> {
+        let mut exponents = self.0;
+        let mut idx = 0;
+        while idx < exponents.len() {
+            let Some(exponent) = other.0[idx].checked_mul(sign) else {
+                return None;
+            };
+            let Some(exponent) = exponents[idx].checked_add(exponent) else {
+                return None;
+            };
+            exponents[idx] = exponent;
+            idx += 1;
+        }
+        Some(Dim(exponents))
+    }
+
+    pub const fn checked_mul(self, other: Dim) -> Option<Dim> {
+        self.combine(other, 1)
+    }
+
+    pub const fn checked_div(self, other: Dim) -> Option<Dim> {
+        self.combine(other, -1)
+    }
+
+    /// Product of dimensions of the named units, whose exponents are small.
+    const fn mul(self, other: Dim) -> Dim {
+        match self.checked_mul(other) {
+            Some(dim) => dim,
+            None => panic!("Unit exponent out of range"),
+        }
+    }
+
+    const fn div(self, other: Dim) -> Dim {
+        match self.checked_div(other) {
+            Some(dim) => dim,
+            None => panic!("Unit exponent out of range"),
+        }
+    }
+}
+
+impl std::fmt::Display for Dim {
+    /// Writes a named unit such as `N` if one matches, base units such as `m/s²` otherwise.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(&(name, ..)) = UNITS
+            .iter()
+            .find(|&&(_, scale, dim)| scale == 1.0 && dim == *self)
+        {
+            return f.write_str(name);
+        }
+
+        let part = |unit: &str, exponent: i8| match exponent {
+            1 => unit.to_owned(),
+            _ => format!("{unit}{}", superscript(exponent)),
+        };
+        let factors = |positive: bool| {
+            BASE_UNITS
+                .iter()
+                .zip(self.0)
+                .filter(|&(_, exponent)| exponent != 0 && (exponent > 0) == positive)
+                .map(|(unit, exponent)| part(unit, if positive { exponent } else { -exponent }))
+                .collect::<Vec<_>>()
+        };
+
+        let (numerator, denominator) = (factors(true), factors(false));
+        match (&*numerator, &*denominator) {
+            ([], _) => {
+                let factors = BASE_UNITS
+                    .iter()
+                    .zip(self.0)
+                    .filter(|&(_, exponent)| exponent != 0)
+                    .map(|(unit, exponent)| part(unit, exponent))
+                    .collect::<Vec<_>>();
+                f.write_str(&factors.join("·"))
+            }
+            (_, []) => f.write_str(&numerator.join("·")),
+            (_, [single]) => write!(f, "{}/{single}", numerator.join("·")),
+            _ => write!(f, "{}/({})", numerator.join("·"), denominator.join("·")),
+        }
+    }
+}
+
+fn superscript(exponent: i8) -> String {
+    exponent
+        .to_string()
+        .chars()
+        .map(|c| match c {
+            '-' => '⁻',
+            '0' => '⁰',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '4' => '⁴',
+            '5' => '⁵',
+            '6' => '⁶',
+            '7' => '⁷',
+            '8' => '⁸',
+            _ => '⁹',
+        })
+        .collect()
+}
+
+const MASS: Dim = Dim([1, 0, 0, 0, 0]);
+const LENGTH: Dim = Dim([0, 1, 0, 0, 0]);
+const TIME: Dim = Dim([0, 0, 1, 0, 0]);
+const CURRENT: Dim = Dim([0, 0, 0, 1, 0]);
+const TEMPERATURE: Dim = Dim([0, 0, 0, 0, 1]);
+const FORCE: Dim = MASS.mul(LENGTH).div(TIME).div(TIME);
+const ENERGY: Dim = FORCE.mul(LENGTH);
+const POWER: Dim = ENERGY.div(TIME);
+
+/// Units accepted after numbers, with their size in base units.
+const UNITS: &[(&str, f64, Dim)] = &[
+    ("m", 1.0, LENGTH),
+    ("km", 1e3, LENGTH),
+    ("cm", 1e-2, LENGTH),
+    ("mm", 1e-3, LENGTH),
+    ("um", 1e-6, LENGTH),
+    ("kg", 1.0, MASS),
+    ("g", 1e-3, MASS),
+    ("mg", 1e-6, MASS),
+    ("t", 1e3, MASS),
+    ("s", 1.0, TIME),
+    ("ms", 1e-3, TIME),
+    ("min", 60.0, TIME),
+    ("h", 3600.0, TIME),
+    ("A", 1.0, CURRENT),
+    ("mA", 1e-3, CURRENT),
+    ("K", 1.0, TEMPERATURE),
+    ("N", 1.0, FORCE),
+    ("kN", 1e3, FORCE),
+    ("J", 1.0, ENERGY),
+    ("kJ", 1e3, ENERGY),
+    ("W", 1.0, POWER),
+    ("kW", 1e3, POWER),
+    ("Pa", 1.0, FORCE.div(LENGTH).div(LENGTH)),
+    ("kPa", 1e3, FORCE.div(LENGTH).div(LENGTH)),
+    ("Hz", 1.0, Dim::NONE.div(TIME)),
+    ("V", 1.0, POWER.div(CURRENT)),
+    ("L", 1e-3, LENGTH.mul(LENGTH).mul(LENGTH)),
+];
+
+/// Size in base units and dimension of the unit named `name`.
+pub fn unit(name: &str) -> Option<(f64, Dim)> {
+    UNITS
+        .iter()
+        .find(|&&(unit, ..)| unit == name)
+        .map(|&(_, scale, dim)| (scale, dim))
+}
+
+/// Value given in `unit` in base units if the unit is known, as a plain number otherwise.
+pub fn quantity(value: f64, unit: &str) -> Quantity {
+    match self::unit(unit) {
+        Some((scale, dim)) => Quantity {
+            dim,
+            ..Quantity::number(value * scale)
+        },
+        None => Quantity::number(value),
+    }
+}
+
+/// Number with a dimension, in base units, and the bounds it is known within.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quantity {
+    pub value: Real,
+    /// Imaginary part of complex values.
+    pub imag: Option<Real>,
+    pub dim: Dim,
+    /// Range of possible values when computed from intervals.
+    pub bounds: Option<Bounds>,
+}
+
+impl Quantity {
+    pub const fn number(value: f64) -> Self {
+        Quantity {
+            value: Real::Float(value),
+            imag: None,
+            dim: Dim::NONE,
+            bounds: None,
+        }
+    }
+
+    /// Value as a complex number, with a zero imaginary part if it is real.
+    fn complex(self) -> Complex {
+        Complex {
+            re: self.value,
+            im: self.imag.unwrap_or(Real::zero(self.value.backend())),
+        }
+    }
+
+    /// Result of a complex operation, which has no bounds.
+    fn complex_op(
+        self,
+        other: Quantity,
+        dim: Dim,
+        op: impl FnOnce(Complex, Complex) -> Result<Complex, String>,
+    ) -> Result<Quantity, String> {
+        if self.bounds.is_some() || other.bounds.is_some() {
+            return Err("Intervals can't be complex".to_owned());
+        }
+        let value = op(self.complex(), other.complex())?;
+        Ok(Quantity {
+            value: value.re,
+            imag: Some(value.im),
+            dim,
+            bounds: None,
+        })
+    }
+
+    const fn is_complex(self, other: Quantity) -> bool {
+        self.imag.is_some() || other.imag.is_some()
+    }
+
+    /// Bounds of both operands if either has them.
+    fn bounds_with(self, other: Quantity) -> Option<(Bounds, Bounds)> {
+        let point = |quantity: Quantity| {
+            quantity
+                .bounds
+                .unwrap_or(Bounds::point(quantity.value.to_f64()))
+        };
+        (self.bounds.is_some() || other.bounds.is_some()).then(|| (point(self), point(other)))
+    }
+
+    pub fn neg(self) -> Result<Quantity, String> {
+        Ok(Quantity {
+            value: self.value.neg()?,
+            imag: self.imag.map(Real::neg).transpose()?,
+            bounds: self.bounds.map(Bounds::neg),
+            ..self
+        })
+    }
+
+    /// Adds or subtracts quantities of the same dimension.
+    pub fn add(self, other: Quantity, sign: f64) -> Result<Quantity, String> {
+        if self.dim != other.dim {
+            let verb = if sign < 0.0 { "subtract" } else { "add" };
+            return Err(format!(
+                "Can't {verb} {} and {}",
+                dim_name(self.dim),
+                dim_name(other.dim)
+            ));
+        }
+        let other = if sign < 0.0 { other.neg()? } else { other };
+        if self.is_complex(other) {
+            return self.complex_op(other, self.dim, Complex::add);
+        }
+        Ok(Quantity {
+            value: self.value.add(other.value)?,
+            imag: None,
+            dim: self.dim,
+            bounds: self.bounds_with(other).map(|(lhs, rhs)| lhs.add(rhs)),
+        })
+    }
+
+    pub fn mul(self, other: Quantity) -> Result<Quantity, String> {
+        let dim = self.dim.checked_mul(other.dim).ok_or_else(|| {
+            format!(
+                "Can't multiply {} and {}, the unit's exponents are too large",
+                dim_name(self.dim),
+                dim_name(other.dim)
+            )
+        })?;
+        if self.is_complex(other) {
+            return self.complex_op(other, dim, Complex::mul);
+        }
+        Ok(Quantity {
+            value: self.value.mul(other.value)?,
+            imag: None,
+            dim,
+            bounds: self.bounds_with(other).map(|(lhs, rhs)| lhs.mul(rhs)),
+        })
+    }
+
+    pub fn div(self, other: Quantity) -> Result<Quantity, String> {
+        let dim = self.dim.checked_div(other.dim).ok_or_else(|| {
+            format!(
+                "Can't divide {} by {}, the unit's exponents are too large",
+                dim_name(self.dim),
+                dim_name(other.dim)
+            )
+        })?;
+        if self.is_complex(other) {
+            return self.complex_op(other, dim, Complex::div);
+        }
+        Ok(Quantity {
+            value: self.value.div(other.value)?,
+            imag: None,
+            dim,
+            bounds: self
+                .bounds_with(other)
+                .map(|(lhs, rhs)| lhs.div(rhs))
+                .transpose()?,
+        })
+    }
+
+    /// Real part.
+    pub const fn re(self) -> Quantity {
+        Quantity { imag: None, ..self }
+    }
+
+    /// Imaginary part, zero for real values.
+    pub fn im(self) -> Quantity {
+        Quantity {
+            value: self.complex().im,
+            imag: None,
+            dim: self.dim,
+            bounds: None,
+        }
+    }
+
+    /// Distance from zero.
+    pub fn abs(self) -> Result<Quantity, String> {
+        Ok(Quantity {
+            value: match self.imag {
+                Some(_) => self.complex().abs()?,
+                None => self.value.abs()?,
+            },
+            imag: None,
+            dim: self.dim,
+            bounds: self.bounds.map(Bounds::abs),
+        })
+    }
+
+    /// Angle from the positive real axis in radians, as a plain number.
+    pub fn arg(self) -> Result<Quantity, String> {
+        Ok(Quantity {
+            value: self.complex().arg()?,
+            imag: None,
+            dim: Dim::NONE,
+            bounds: None,
+        })
+    }
+
+    /// Complex conjugate.
+    pub fn conj(self) -> Result<Quantity, String> {
+        Ok(Quantity {
+            imag: self.imag.map(Real::neg).transpose()?,
+            ..self
+        })
+    }
+}
+
+fn dim_name(dim: Dim) -> String {
+    if dim == Dim::NONE {
+        "a plain number".to_owned()
+    } else {
+        dim.to_string()
+    }
+}
+
+impl std::fmt::Display for Quantity {
+    /// Writes the bounds if known, or the value, rounded to the formatter's precision.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.bounds, self.imag) {
+            (Some(bounds), _) => std::fmt::Display::fmt(&bounds, f)?,
+            (None, Some(_)) if self.dim != Dim::NONE => {
+                f.write_str("(")?;
+                std::fmt::Display::fmt(&self.complex(), f)?;
+                f.write_str(")")?;
+            }
+            (None, Some(_)) => std::fmt::Display::fmt(&self.complex(), f)?,
+            (None, None) => std::fmt::Display::fmt(&self.value, f)?,
+        }
+        if self.dim != Dim::NONE {
+            write!(f, " {}", self.dim)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_exponent_overflow() {
+        let cubed = LENGTH.mul(LENGTH).mul(LENGTH);
+        assert_eq!(cubed.checked_div(LENGTH), Some(LENGTH.mul(LENGTH)));
+        assert_eq!(Dim([0, 127, 0, 0, 0]).checked_mul(LENGTH), None);
+        assert_eq!(Dim::NONE.checked_div(Dim([0, 0, -128, 0, 0])), None);
+
+        let huge = Quantity {
+            dim: Dim([0, 100, 0, 0, 0]),
+            ..Quantity::number(2.0)
+        };
+        assert_eq!(
+            huge.mul(huge).unwrap_err(),
+            "Can't multiply m¹⁰⁰ and m¹⁰⁰, the unit's exponents are too large"
+        );
+        assert!(huge.div(huge).is_ok());
+    }
+}
@@ -0,0 +1,222 @@
+//! Vector and color nodes.
+
+use egui::Color32;
+
+use super::{PinCompat, Value, PIN_COLOR, PIN_VEC, PIN_VEC3};
+
+node_ops! {
+    pub enum VectorOp {
+        ComposeVec2: "Compose Vec2", "Builds vector from its components";
+        ComposeVec3: "Compose Vec3", "Builds vector from its components";
+        ComposeVec4: "Compose Vec4", "Builds vector from its components";
+        ComposeColor: "Compose Color", "Builds color from red, green, blue and alpha in 0..1";
+        DecomposeVec2: "Decompose Vec2", "Outputs each component of the vector";
+        DecomposeVec3: "Decompose Vec3", "Outputs each component of the vector";
+        DecomposeVec4: "Decompose Vec4", "Outputs each component of the vector";
+        DecomposeColor: "Decompose Color",
+            "Outputs red, green, blue and alpha of the color in 0..1";
+        Dot: "Dot", "Outputs dot product of two vectors";
+        Cross: "Cross", "Outputs cross product of two 3D vectors";
+        Length as "VectorLength": "Vector Length", "Outputs length of the vector";
+        Normalize: "Normalize", "Scales vector to length 1";
+        /// Interpolates from `A` at `T` = 0 to `B` at `T` = 1.
+        Lerp: "Lerp", "Interpolates between two vectors or colors";
+    }
+}
+
+impl VectorOp {
+    /// Number of components of the vector or color this node builds or splits.
+    const fn components(self) -> usize {
+        match self {
+            VectorOp::ComposeVec2 | VectorOp::DecomposeVec2 => 2,
+            VectorOp::ComposeVec3 | VectorOp::DecomposeVec3 => 3,
+            _ => 4,
+        }
+    }
+
+    /// Labels of the components of the vector or color this node builds or splits.
+    fn component_labels(self) -> &'static [&'static str] {
+        match self {
+            VectorOp::ComposeColor | VectorOp::DecomposeColor => &["R", "G", "B", "A"],
+            _ => &["x", "y", "z", "w"][..self.components()],
+        }
+    }
+
+    /// Labels and initial values of the inputs.
+    fn inputs(self) -> Vec<(&'static str, Value)> {
+        let vec3 = || Value::Vector(vec![0.0; 3]);
+        match self {
+            VectorOp::ComposeVec2 | VectorOp::ComposeVec3 | VectorOp::ComposeVec4 => self
+                .component_labels()
+                .iter()
+                .map(|&label| (label, Value::Number(0.0)))
+                .collect(),
+            VectorOp::ComposeColor => vec![
+                ("R", Value::Number(0.0)),
+                ("G", Value::Number(0.0)),
+                ("B", Value::Number(0.0)),
+                ("A", Value::Number(1.0)),
+            ],
+            VectorOp::DecomposeVec2 | VectorOp::DecomposeVec3 | VectorOp::DecomposeVec4 => {
+                vec![("Vector", Value::Vector(vec![0.0; self.components()]))]
+            }
+            VectorOp::DecomposeColor => vec![("Color", Value::Color([0.0, 0.0, 0.0, 1.0]))],
+            VectorOp::Dot | VectorOp::Cross => vec![("A", vec3()), ("B", vec3())],
+            VectorOp::Length | VectorOp::Normalize => vec![("Vector", vec3())],
+            VectorOp::Lerp => vec![("A", vec3()), ("B", vec3()), ("T", Value::Number(0.5))],
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct VectorNode {
+    pub(super) op: VectorOp,
+    /// Components being composed, or the vectors and colors the operation reads.
+    pub(super) inputs: Vec<Value>,
+}
+
+impl VectorNode {
+    pub fn new(op: VectorOp) -> Self {
+        VectorNode {
+            op,
+            inputs: op.inputs().into_iter().map(|(_, value)| value).collect(),
+        }
+    }
+
+    pub(super) fn label_in(&self, idx: usize) -> &str {
+        self.op.inputs()[idx].0
+    }
+
+    pub(super) fn outputs(&self) -> usize {
+        match self.op {
+            VectorOp::DecomposeVec2
+            | VectorOp::DecomposeVec3
+            | VectorOp::DecomposeVec4
+            | VectorOp::DecomposeColor => self.op.components(),
+            _ => 1,
+        }
+    }
+
+    pub(super) fn label_out(&self, idx: usize) -> &str {
+        match self.op {
+            VectorOp::DecomposeVec2
+            | VectorOp::DecomposeVec3
+            | VectorOp::DecomposeVec4
+            | VectorOp::DecomposeColor => self.op.component_labels()[idx],
+            _ => "",
+        }
+    }
+
+    pub(super) fn in_compat(&self, idx: usize) -> PinCompat {
+        match self.op {
+            VectorOp::Dot | VectorOp::Length | VectorOp::Normalize => PIN_VEC,
+            VectorOp::Cross => PIN_VEC3,
+            VectorOp::Lerp if idx < 2 => PIN_VEC | PIN_COLOR,
+            _ => self.inputs[idx].compat(),
+        }
+    }
+
+    /// Normalize and Lerp output the type of their input.
+    pub(super) fn out_compat(&self, idx: usize) -> PinCompat {
+        self.value_out(idx).compat()
+    }
+
+    pub(super) fn value_out(&self, idx: usize) -> Value {
+        let number_in = |idx: usize| self.inputs[idx].number();
+        let vector_in = |idx: usize| self.inputs[idx].components();
+
+        match self.op {
+            VectorOp::ComposeVec2 | VectorOp::ComposeVec3 | VectorOp::ComposeVec4 => {
+                Value::Vector((0..self.inputs.len()).map(number_in).collect())
+            }
+            VectorOp::ComposeColor => Value::Color([0, 1, 2, 3].map(number_in)),
+            VectorOp::DecomposeVec2
+            | VectorOp::DecomposeVec3
+            | VectorOp::DecomposeVec4
+            | VectorOp::DecomposeColor => {
+                Value::Number(vector_in(0).get(idx).copied().unwrap_or(0.0))
+            }
+            VectorOp::Dot => Value::Number(dot(&vector_in(0), &vector_in(1))),
+            VectorOp::Cross => {
+                let (mut a, mut b) = (vector_in(0), vector_in(1));
+                a.resize(3, 0.0);
+                b.resize(3, 0.0);
+                Value::Vector(vec![
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                ])
+            }
+            VectorOp::Length => Value::Number(dot(&vector_in(0), &vector_in(0)).sqrt()),
+            VectorOp::Normalize => {
+                let v = vector_in(0);
+                let length = dot(&v, &v).sqrt();
+                if length == 0.0 {
+                    Value::Vector(v)
+                } else {
+                    Value::Vector(v.iter().map(|c| c / length).collect())
+                }
+            }
+            VectorOp::Lerp => {
+                let t = number_in(2);
+                let b = vector_in(1);
+                let out = vector_in(0)
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, a)| a + (b.get(idx).copied().unwrap_or(0.0) - a) * t)
+                    .collect::<Vec<_>>();
+                match self.inputs[0] {
+                    Value::Color(_) => Value::Color(std::array::from_fn(|idx| out[idx])),
+                    _ => Value::Vector(out),
+                }
+            }
+        }
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Converts unmultiplied sRGB components in 0..1 to [`Color32`].
+pub fn color32(color: [f64; 4]) -> Color32 {
+    let [r, g, b, a] = color.map(to_u8);
+    Color32::from_rgba_unmultiplied(r, g, b, a)
+}
+
+/// Shows color picker button, returns whether the color changed.
+pub fn edit_color(ui: &mut egui::Ui, color: &mut [f64; 4]) -> bool {
+    let mut srgba = color.map(to_u8);
+    let changed = ui
+        .color_edit_button_srgba_unmultiplied(&mut srgba)
+        .changed();
+    if changed {
+        *color = srgba.map(|c| f64::from(c) / 255.0);
+    }
+    changed
+}
+
+/// Formats color as `#rrggbbaa`.
+pub fn color_hex(color: [f64; 4]) -> String {
+    let [r, g, b, a] = color.map(to_u8);
+    format!("#{r:02x}{g:02x}{b:02x}{a:02x}")
+}
+
+/// Parses color from `#rrggbb` or `#rrggbbaa`.
+pub fn parse_color_hex(hex: &str) -> Option<[f64; 4]> {
+    let hex = hex.strip_prefix('#')?;
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+    let mut color = [0.0, 0.0, 0.0, 1.0];
+    for (idx, component) in color.iter_mut().enumerate().take(hex.len() / 2) {
+        let byte = u8::from_str_radix(&hex[idx * 2..idx * 2 + 2], 16).ok()?;
+        *component = f64::from(byte) / 255.0;
+    }
+    Some(color)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn to_u8(component: f64) -> u8 {
+    (component.clamp(0.0, 1.0) * 255.0).round() as u8
+}